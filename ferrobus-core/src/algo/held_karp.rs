@@ -0,0 +1,173 @@
+//! Held-Karp dynamic programming over subsets of a fixed-start, open-path
+//! visiting-order problem, generic over whatever "cost" and per-hop
+//! "payload" each caller cares about. Factors out the subset-DP/predecessor
+//! table/backtrack pattern shared by [`crate::routing::tour`]'s
+//! time-dependent multi-waypoint tour, [`crate::routing::raptor::traced::via`]'s
+//! via-waypoint ordering, and `optimal_tour`'s static travel-time-matrix
+//! solver, so each only has to supply its own notion of an edge's cost.
+
+/// Dynamic-programming table over subsets of `0..n` "rest" points reachable
+/// from an implicit fixed start. `dp[mask][j]` is the best accumulated cost
+/// of having started at the fixed start, visited exactly the rest-points in
+/// `mask` (bit `k` meaning rest-point `k`), and ended at rest-point `j`;
+/// `predecessor[mask][j]` carries the previous rest-point (or `usize::MAX`
+/// for the fixed start) and the payload of that final hop, so a winning path
+/// can be walked back without recomputing it.
+struct Table<C, P> {
+    n: usize,
+    dp: Vec<Vec<Option<C>>>,
+    predecessor: Vec<Vec<Option<(usize, P)>>>,
+}
+
+impl<C, P> Table<C, P>
+where
+    C: Copy + Ord,
+{
+    /// Builds the table: `start_cost(j)` is the cost/payload of the first
+    /// hop from the fixed start directly to rest-point `j`; `edge_cost(j,
+    /// cost_at_j, k)` is the cost/payload of extending from `j` (already
+    /// reached at accumulated cost `cost_at_j`) onward to unvisited `k`.
+    /// Either returns `None` when that hop isn't possible.
+    fn build(
+        n: usize,
+        mut start_cost: impl FnMut(usize) -> Option<(C, P)>,
+        mut edge_cost: impl FnMut(usize, C, usize) -> Option<(C, P)>,
+    ) -> Self {
+        let full_mask = 1usize << n;
+        let mut dp: Vec<Vec<Option<C>>> = vec![vec![None; n]; full_mask];
+        let mut predecessor: Vec<Vec<Option<(usize, P)>>> =
+            (0..full_mask).map(|_| (0..n).map(|_| None).collect()).collect();
+
+        for j in 0..n {
+            if let Some((cost, payload)) = start_cost(j) {
+                dp[1 << j][j] = Some(cost);
+                predecessor[1 << j][j] = Some((usize::MAX, payload));
+            }
+        }
+
+        for mask in 1..full_mask {
+            for j in 0..n {
+                if mask & (1 << j) == 0 {
+                    continue;
+                }
+                let Some(cost_at_j) = dp[mask][j] else {
+                    continue;
+                };
+                for k in 0..n {
+                    if mask & (1 << k) != 0 {
+                        continue;
+                    }
+                    let Some((candidate, payload)) = edge_cost(j, cost_at_j, k) else {
+                        continue;
+                    };
+                    let next_mask = mask | (1 << k);
+                    if dp[next_mask][k].is_none_or(|best| candidate < best) {
+                        dp[next_mask][k] = Some(candidate);
+                        predecessor[next_mask][k] = Some((j, payload));
+                    }
+                }
+            }
+        }
+
+        Self { n, dp, predecessor }
+    }
+
+    /// Walks `predecessor` back from `(full_mask, last)` to the fixed start,
+    /// returning the visited rest-points and their hop payloads in visiting
+    /// order.
+    fn backtrack(&mut self, last: usize) -> (Vec<usize>, Vec<P>) {
+        let full_mask = (1usize << self.n) - 1;
+        let mut mask = full_mask;
+        let mut current = last;
+        let mut order = vec![current];
+        let mut payloads = Vec::with_capacity(self.n);
+
+        loop {
+            let (prev, payload) = self.predecessor[mask][current].take().unwrap();
+            payloads.push(payload);
+            if prev == usize::MAX {
+                break;
+            }
+            mask &= !(1 << current);
+            current = prev;
+            order.push(current);
+        }
+        order.reverse();
+        payloads.reverse();
+
+        (order, payloads)
+    }
+}
+
+/// Held-Karp dynamic programming over an open-path visiting-order problem:
+/// starting at a fixed (implicit) start, visit every one of `n` "rest"
+/// points exactly once at minimum accumulated cost, ending wherever is
+/// cheapest rather than at a fixed destination.
+///
+/// `start_cost` and `edge_cost` are as in [`Table::build`]. `C` is the
+/// accumulated cost type (e.g. total time or an arrival clock), compared
+/// with [`Ord`]; `P` is whatever per-hop payload the caller wants back (a
+/// routed journey, or `()` if the visiting order alone is enough).
+///
+/// Returns the visited rest-points in visiting order, each hop's payload
+/// (one shorter than the order, since the first hop's payload covers
+/// start -> `order[0]`), and the winning total cost — or `None` if no
+/// complete tour exists.
+pub fn held_karp<C, P>(
+    n: usize,
+    start_cost: impl FnMut(usize) -> Option<(C, P)>,
+    edge_cost: impl FnMut(usize, C, usize) -> Option<(C, P)>,
+) -> Option<(Vec<usize>, Vec<P>, C)>
+where
+    C: Copy + Ord,
+{
+    let mut table = Table::build(n, start_cost, edge_cost);
+    let full_mask = (1usize << n) - 1;
+
+    let (last, total_cost) = (0..n)
+        .filter_map(|j| table.dp[full_mask][j].map(|cost| (j, cost)))
+        .min_by_key(|&(_, cost)| cost)?;
+
+    let (order, payloads) = table.backtrack(last);
+    Some((order, payloads, total_cost))
+}
+
+/// Same as [`held_karp`], but every complete visiting order additionally
+/// pays a `finish_cost(j, cost_at_j)` hop from its last rest-point `j` to a
+/// fixed destination that isn't itself one of the `n` rest-points (e.g. a
+/// via-routing target reached only after every waypoint has been visited).
+/// Every candidate last rest-point is evaluated with its finish hop before
+/// picking the overall best, since the cheapest visiting order ignoring the
+/// finish hop need not stay cheapest once it's added.
+///
+/// The returned payloads end with the finish hop's payload.
+pub fn held_karp_with_finish<C, P>(
+    n: usize,
+    start_cost: impl FnMut(usize) -> Option<(C, P)>,
+    edge_cost: impl FnMut(usize, C, usize) -> Option<(C, P)>,
+    mut finish_cost: impl FnMut(usize, C) -> Option<(C, P)>,
+) -> Option<(Vec<usize>, Vec<P>, C)>
+where
+    C: Copy + Ord,
+{
+    let mut table = Table::build(n, start_cost, edge_cost);
+    let full_mask = (1usize << n) - 1;
+
+    let mut best: Option<(usize, C, P)> = None;
+    for j in 0..n {
+        let Some(cost_at_j) = table.dp[full_mask][j] else {
+            continue;
+        };
+        let Some((finish_total, finish_payload)) = finish_cost(j, cost_at_j) else {
+            continue;
+        };
+        if best.as_ref().is_none_or(|&(_, best_cost, _)| finish_total < best_cost) {
+            best = Some((j, finish_total, finish_payload));
+        }
+    }
+
+    let (last, total_cost, finish_payload) = best?;
+    let (order, mut payloads) = table.backtrack(last);
+    payloads.push(finish_payload);
+    Some((order, payloads, total_cost))
+}
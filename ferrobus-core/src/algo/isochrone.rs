@@ -3,27 +3,55 @@
 //! alternative approach to calculate isochrones using H3 hexagonal
 //! grid cells as a `index`.
 
+use std::path::Path;
+
 use geo::{MultiPolygon, Point, Polygon};
+use geojson::{Feature, FeatureCollection, Geometry};
+use hashbrown::HashMap;
 use rayon::prelude::*;
 
 use h3o::{
-    CellIndex, LatLng, Resolution,
     geom::{ContainmentMode, SolventBuilder, TilerBuilder},
+    CellIndex, LatLng, Resolution,
 };
 
+use crate::routing::raptor::{AccessibilityFilter, RealtimeUpdate};
+use crate::{multimodal_routing_one_to_many, TransitPoint};
 use crate::{Error, Time, TransitModel};
-use crate::{TransitPoint, multimodal_routing_one_to_many};
 
 /// Index for isochrone calculation covering a specific area
 /// It contains a grid of hexagonal H3 cells and their respective
 /// transit points.
-#[derive(Debug, Clone)]
+///
+/// Building one snaps every hex centroid to the transit network, which
+/// dominates `IsochroneIndex::new`'s cost; [`Self::save_to_path`] /
+/// [`Self::load_from_path`] let callers cache that work across process
+/// restarts instead of re-snapping on every start.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct IsochroneIndex {
     pub grid: Vec<CellIndex>,
     transit_points: Vec<TransitPoint>,
     resoulution: u8,
 }
 
+impl IsochroneIndex {
+    /// Write this index to `path` as a compact binary file.
+    pub fn save_to_path(&self, path: &Path) -> Result<(), Error> {
+        let file = std::fs::File::create(path)?;
+        bincode::serialize_into(std::io::BufWriter::new(file), self)
+            .map_err(|e| Error::IsochroneError(format!("Failed to serialize isochrone index: {e}")))
+    }
+
+    /// Load an index previously written with [`Self::save_to_path`],
+    /// skipping the parallel centroid-snapping that [`Self::new`] performs.
+    pub fn load_from_path(path: &Path) -> Result<Self, Error> {
+        let file = std::fs::File::open(path)?;
+        bincode::deserialize_from(std::io::BufReader::new(file)).map_err(|e| {
+            Error::IsochroneError(format!("Failed to deserialize isochrone index: {e}"))
+        })
+    }
+}
+
 impl IsochroneIndex {
     pub fn len(&self) -> usize {
         self.grid.len()
@@ -61,14 +89,27 @@ impl IsochroneIndex {
     }
 }
 
-pub fn calculate_isochrone(
+/// Travel time from `start` to every reached cell in `index.grid`; cells
+/// that could not be reached within `max_transfers` are omitted rather than
+/// dissolved into a polygon straight away, so callers can re-slice the same
+/// routing pass by cutoff or aggregate it into an accessibility score.
+///
+/// `realtime`, if given, is the same live GTFS-RT delay/cancellation overlay
+/// `traced_raptor` accepts, so an isochrone can reflect the current state of
+/// service instead of only the static schedule.
+///
+/// `accessibility`, if given, restricts reachability to trips and stops that
+/// satisfy the wheelchair-accessibility constraint, the same way it gates
+/// boarding in `traced_raptor`.
+pub fn calculate_reachability(
     transit_data: &TransitModel,
     start: &TransitPoint,
     departure_time: Time,
     max_transfers: usize,
-    cutoff: Time,
     index: &IsochroneIndex,
-) -> Result<MultiPolygon, Error> {
+    realtime: Option<&RealtimeUpdate>,
+    accessibility: Option<&AccessibilityFilter>,
+) -> Result<Vec<(CellIndex, Time)>, Error> {
     let snapped_centroids = &index.transit_points;
     let grid = &index.grid;
 
@@ -78,22 +119,196 @@ pub fn calculate_isochrone(
         snapped_centroids,
         departure_time,
         max_transfers,
+        realtime,
+        accessibility,
     )?;
 
-    let reached_cells: Vec<CellIndex> = routing_results
+    Ok(routing_results
         .iter()
         .enumerate()
-        .filter_map(|(index, result)| {
-            result
-                .as_ref()
-                .filter(|r| r.travel_time < cutoff)
-                .map(|_| grid[index])
+        .filter_map(|(idx, result)| result.as_ref().map(|r| (grid[idx], r.travel_time)))
+        .collect())
+}
+
+pub fn calculate_isochrone(
+    transit_data: &TransitModel,
+    start: &TransitPoint,
+    departure_time: Time,
+    max_transfers: usize,
+    cutoff: Time,
+    index: &IsochroneIndex,
+    realtime: Option<&RealtimeUpdate>,
+    accessibility: Option<&AccessibilityFilter>,
+) -> Result<MultiPolygon, Error> {
+    let reachability = calculate_reachability(
+        transit_data,
+        start,
+        departure_time,
+        max_transfers,
+        index,
+        realtime,
+        accessibility,
+    )?;
+
+    dissolve_cells(
+        reachability
+            .into_iter()
+            .filter(|&(_, travel_time)| travel_time < cutoff)
+            .map(|(cell, _)| cell),
+    )
+}
+
+/// Nested isochrones for several `cutoffs` computed from a single routing
+/// pass instead of one `multimodal_routing_one_to_many` call per threshold.
+/// Each band contains every cell reachable within its own cutoff, so bands
+/// are nested (a larger cutoff's polygon contains all smaller ones), not
+/// disjoint rings between consecutive cutoffs.
+pub fn calculate_banded_isochrone(
+    transit_data: &TransitModel,
+    start: &TransitPoint,
+    departure_time: Time,
+    max_transfers: usize,
+    cutoffs: &[Time],
+    index: &IsochroneIndex,
+    realtime: Option<&RealtimeUpdate>,
+    accessibility: Option<&AccessibilityFilter>,
+) -> Result<Vec<MultiPolygon>, Error> {
+    let reachability = calculate_reachability(
+        transit_data,
+        start,
+        departure_time,
+        max_transfers,
+        index,
+        realtime,
+        accessibility,
+    )?;
+
+    cutoffs
+        .iter()
+        .map(|&cutoff| {
+            dissolve_cells(
+                reachability
+                    .iter()
+                    .filter(|&&(_, travel_time)| travel_time < cutoff)
+                    .map(|&(cell, _)| cell),
+            )
+        })
+        .collect()
+}
+
+/// [`calculate_banded_isochrone`], but returned as a single `GeoJSON`
+/// `FeatureCollection` with one feature per cutoff (each carrying its cutoff
+/// as a `max_time` property) instead of a `Vec<MultiPolygon>`, so callers
+/// that want GeoJSON don't have to zip the bands back up with `cutoffs`
+/// themselves.
+pub fn calculate_banded_isochrone_geojson(
+    transit_data: &TransitModel,
+    start: &TransitPoint,
+    departure_time: Time,
+    max_transfers: usize,
+    cutoffs: &[Time],
+    index: &IsochroneIndex,
+    realtime: Option<&RealtimeUpdate>,
+    accessibility: Option<&AccessibilityFilter>,
+) -> Result<FeatureCollection, Error> {
+    let bands = calculate_banded_isochrone(
+        transit_data,
+        start,
+        departure_time,
+        max_transfers,
+        cutoffs,
+        index,
+        realtime,
+        accessibility,
+    )?;
+
+    let features = cutoffs
+        .iter()
+        .zip(bands)
+        .map(|(&max_time, polygon)| {
+            let value = serde_json::json!({
+                "type": "Feature",
+                "geometry": Geometry::new((&polygon).into()),
+                "properties": { "max_time": max_time }
+            });
+            Feature::from_json_value(value)
+                .expect("band feature built from a fixed, known-valid shape")
         })
         .collect();
 
+    Ok(FeatureCollection {
+        features,
+        bbox: None,
+        foreign_members: None,
+    })
+}
+
+/// Cumulative-opportunity accessibility score from `start`: the summed
+/// `weights` (jobs, population, ...) of every grid cell reached within
+/// `cutoff`. Cells absent from `weights` contribute nothing.
+pub fn cumulative_accessibility(
+    transit_data: &TransitModel,
+    start: &TransitPoint,
+    departure_time: Time,
+    max_transfers: usize,
+    cutoff: Time,
+    weights: &HashMap<CellIndex, f64>,
+    index: &IsochroneIndex,
+    realtime: Option<&RealtimeUpdate>,
+    accessibility: Option<&AccessibilityFilter>,
+) -> Result<f64, Error> {
+    let reachability = calculate_reachability(
+        transit_data,
+        start,
+        departure_time,
+        max_transfers,
+        index,
+        realtime,
+        accessibility,
+    )?;
+
+    Ok(reachability
+        .into_iter()
+        .filter(|&(_, travel_time)| travel_time < cutoff)
+        .filter_map(|(cell, _)| weights.get(&cell))
+        .sum())
+}
+
+/// [`cumulative_accessibility`] for several start points in parallel, the
+/// accessibility-scoring counterpart to [`bulk_isochrones`].
+pub fn bulk_accessibility(
+    transit_data: &TransitModel,
+    starts: &[&TransitPoint],
+    departure_time: Time,
+    max_transfers: usize,
+    cutoff: Time,
+    weights: &HashMap<CellIndex, f64>,
+    index: &IsochroneIndex,
+    realtime: Option<&RealtimeUpdate>,
+    accessibility: Option<&AccessibilityFilter>,
+) -> Result<Vec<f64>, Error> {
+    starts
+        .par_iter()
+        .map(|start| {
+            cumulative_accessibility(
+                transit_data,
+                start,
+                departure_time,
+                max_transfers,
+                cutoff,
+                weights,
+                index,
+                realtime,
+                accessibility,
+            )
+        })
+        .collect()
+}
+
+fn dissolve_cells(cells: impl IntoIterator<Item = CellIndex>) -> Result<MultiPolygon, Error> {
     let solvent = SolventBuilder::new().build();
     solvent
-        .dissolve(reached_cells)
+        .dissolve(cells.into_iter().collect::<Vec<_>>())
         .map_err(|e| Error::IsochroneError(e.to_string()))
 }
 
@@ -104,6 +319,8 @@ pub fn bulk_isochrones(
     max_transfers: usize,
     cutoff: Time,
     index: &IsochroneIndex,
+    realtime: Option<&RealtimeUpdate>,
+    accessibility: Option<&AccessibilityFilter>,
 ) -> Result<Vec<MultiPolygon>, Error> {
     let result: Result<Vec<MultiPolygon>, Error> = starts
         .par_iter()
@@ -115,6 +332,8 @@ pub fn bulk_isochrones(
                 max_transfers,
                 cutoff,
                 index,
+                realtime,
+                accessibility,
             )
         })
         .collect();
@@ -0,0 +1,5 @@
+//! Algorithms that operate over an already-built [`crate::TransitModel`]
+//! rather than constructing one.
+
+pub mod held_karp;
+pub mod isochrone;
@@ -16,4 +16,6 @@ pub enum Error {
     IsochroneError(String),
     #[error("H3 error: {0}")]
     H3Error(#[from] h3o::error::InvalidGeometry),
+    #[error("Operation cancelled")]
+    Cancelled,
 }
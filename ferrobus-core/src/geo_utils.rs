@@ -0,0 +1,18 @@
+//! Small geometry helpers shared across loading and routing code that would
+//! otherwise each hand-roll their own copy.
+
+use geo::Point;
+
+/// Great-circle distance between two lon/lat points, in meters.
+pub(crate) fn haversine_distance_meters(a: Point<f64>, b: Point<f64>) -> f64 {
+    const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+
+    let (lon1, lat1) = (a.x().to_radians(), a.y().to_radians());
+    let (lon2, lat2) = (b.x().to_radians(), b.y().to_radians());
+
+    let d_lat = lat2 - lat1;
+    let d_lon = lon2 - lon1;
+
+    let h = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_METERS * h.sqrt().asin()
+}
@@ -2,10 +2,14 @@ use geo::{ConvexHull, Intersects, MultiPoint};
 use log::info;
 
 use super::config::TransitModelConfig;
+use super::footpaths::precompute_footpaths;
 use super::gtfs::transit_model_from_gtfs;
 use super::osm::create_street_graph;
 use super::transfers::calculate_transfers;
-use crate::{Error, PublicTransitData, TransitModel, model::StreetGraph};
+use crate::{
+    Error, PublicTransitData, TransitModel,
+    model::{ContractionHierarchy, StreetGraph},
+};
 
 /// Creates a transit model based on the provided configuration
 ///
@@ -41,12 +45,23 @@ pub fn create_transit_model(config: &TransitModelConfig) -> Result<TransitModel,
         },
     );
 
-    calculate_transfers(&mut graph);
+    info!("Precomputing stop-to-stop footpaths");
+    let footpaths = precompute_footpaths(&graph, config);
+    info!("Precomputed {} footpaths", footpaths.len());
+
+    calculate_transfers(&mut graph, config, &footpaths);
     info!(
         "Calculated {} transfers between stops",
         &graph.transit_data.transfers.len()
     );
 
+    graph.transit_data.footpaths = footpaths;
+
+    if config.build_contraction_hierarchy {
+        info!("Building contraction hierarchy for the street graph");
+        graph.contraction_hierarchy = Some(ContractionHierarchy::build(&graph.street_graph));
+    }
+
     info!("Transit model created successfully");
     // While processing OSM protobuf data, and during CSV deserialization
     // large amounts of memory are allocated. This memory is not always
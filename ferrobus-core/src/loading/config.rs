@@ -0,0 +1,77 @@
+//! Configuration for building a [`TransitModel`] from OSM and GTFS sources.
+
+use std::path::PathBuf;
+
+use crate::geo_utils::haversine_distance_meters;
+use crate::{RaptorStopId, Time, TransitModel};
+
+/// Predicate consulted for each candidate `(from, to)` stop pair before a
+/// computed transfer is generated, so callers can shape the transfer graph
+/// without post-processing the flat model. Returning `false` skips the
+/// transfer; GTFS-defined transfers are unaffected and continue to override
+/// computed ones regardless of this predicate.
+pub type NeedTransfer = dyn Fn(&TransitModel, RaptorStopId, RaptorStopId) -> bool + Send + Sync;
+
+/// Configuration for [`create_transit_model`](super::create_transit_model)
+pub struct TransitModelConfig {
+    /// Path to the OSM PBF file used to build the street network
+    pub osm_path: PathBuf,
+    /// Paths to GTFS feed directories to load and merge
+    pub gtfs_dirs: Vec<PathBuf>,
+    /// Day of the week used to filter active GTFS services
+    pub day_of_week: String,
+    /// Calendar date used to filter active GTFS services, if provided
+    pub date: Option<chrono::NaiveDate>,
+    /// Maximum walking time, in seconds, allowed for a computed transfer
+    pub max_transfer_time: Time,
+    /// Gates which computed transfers are kept; `None` keeps every transfer
+    /// within `max_transfer_time` (equivalent to [`allow_all_transfers`]).
+    pub need_transfer: Option<Box<NeedTransfer>>,
+    /// Whether to run contraction-hierarchy preprocessing on the street
+    /// graph after it's built. This makes repeated point-to-point walking
+    /// queries (transfer-geometry reconstruction, access/egress legs) much
+    /// faster at the cost of an upfront pass over every node and edge;
+    /// disable it for a short-lived model that only issues a handful of
+    /// queries, since preprocessing may cost more than it saves.
+    pub build_contraction_hierarchy: bool,
+    /// Straight-line cutoff, in meters, for considering a stop pair during
+    /// footpath precomputation, independent of the walking-time cap
+    /// (`max_transfer_time`) applied once a candidate pair's street
+    /// distance is actually searched.
+    pub footpath_max_length: f64,
+    /// Grid cell size, in meters, used to cluster nearby stops during
+    /// footpath precomputation: stops in a cell that snap to the same
+    /// street node share that node's single Dijkstra tree, cutting down
+    /// street searches on a large feed when several stops sit at the same
+    /// junction; stops that snap to distinct nodes still get their own
+    /// search.
+    pub footpath_cluster_size: f64,
+}
+
+/// Keeps every computed transfer within `max_transfer_time`. This is the
+/// default behavior when `need_transfer` is left unset.
+pub fn allow_all_transfers() -> Box<NeedTransfer> {
+    Box::new(|_, _, _| true)
+}
+
+/// Keeps a computed transfer only if the great-circle distance between the
+/// two stops is at most `max_meters`, independent of the walking-time cap
+/// applied during the street-network search.
+pub fn max_distance_transfers(max_meters: f64) -> Box<NeedTransfer> {
+    Box::new(move |model, from, to| {
+        let a = model.transit_data.transit_stop_location(from);
+        let b = model.transit_data.transit_stop_location(to);
+        haversine_distance_meters(a, b) <= max_meters
+    })
+}
+
+/// Discards a computed transfer between two stops that already share a
+/// route, since a rider can already move between them by staying on board
+/// rather than walking.
+pub fn disjoint_route_transfers() -> Box<NeedTransfer> {
+    Box::new(|model, from, to| {
+        let from_routes = model.transit_data.routes_for_stop(from);
+        let to_routes = model.transit_data.routes_for_stop(to);
+        !from_routes.iter().any(|route| to_routes.contains(route))
+    })
+}
@@ -0,0 +1,366 @@
+//! Precomputes stop-to-stop footpaths (walking time and polyline) once at
+//! model build time, instead of recomputing transfer geometry lazily per
+//! journey. Mirrors the footpath-preparation stage of the quetzal
+//! transit-model pipeline.
+//!
+//! Stops within `footpath_cluster_size` meters of each other are grouped
+//! into one grid cell so the street-network search can be batched: cluster
+//! members that snap to the *same* street node share that node's single
+//! Dijkstra tree exactly (the tree is identical regardless of which of them
+//! asked for it), while members that snap to distinct nodes still get their
+//! own tree, since there is no way to derive one member's distances from
+//! another's tree without risking a wrong answer. Each resulting path's
+//! endpoints are snapped to the member's exact location afterward — the
+//! same trick on-demand transfer geometry already uses to paper over a path
+//! that doesn't literally start or end at the stop.
+
+use geo::Coord;
+use hashbrown::HashMap;
+use petgraph::graph::NodeIndex;
+use rayon::prelude::*;
+
+use super::config::TransitModelConfig;
+use super::transfers::snap_stops_to_network;
+use crate::geo_utils::haversine_distance_meters;
+use crate::model::Footpath;
+use crate::routing::dijkstra;
+use crate::routing::dijkstra::WalkingPath;
+use crate::{RaptorStopId, Time, TransitModel};
+
+/// Roughly `meters` converted to degrees of latitude, for building a grid
+/// over stop coordinates. Longitude degrees shrink with latitude, but the
+/// clustering grid only needs cells big enough to group nearby stops, not
+/// an exact metric grid, so a single constant factor is good enough.
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// Precomputes footpaths between every pair of stops within
+/// `config.footpath_max_length` meters (straight-line) and
+/// `graph.meta.max_transfer_time` seconds (walking), keyed by
+/// `(from_stop, to_stop)`.
+pub(crate) fn precompute_footpaths(
+    graph: &TransitModel,
+    config: &TransitModelConfig,
+) -> HashMap<(RaptorStopId, RaptorStopId), Footpath> {
+    let max_transfer_time = graph.meta.max_transfer_time;
+    let stop_nodes = snap_stops_to_network(graph);
+    let cell_size_degrees = (config.footpath_cluster_size / METERS_PER_DEGREE).max(f64::EPSILON);
+
+    let clusters = cluster_stops(graph, &stop_nodes, cell_size_degrees);
+
+    clusters
+        .into_par_iter()
+        .flat_map_iter(|cluster| {
+            footpaths_for_cluster(
+                graph,
+                &stop_nodes,
+                &cluster,
+                max_transfer_time,
+                config.footpath_max_length,
+            )
+        })
+        .collect()
+}
+
+/// Groups stop indices that were successfully snapped to the street
+/// network into grid cells `cell_size_degrees` on a side.
+fn cluster_stops(
+    graph: &TransitModel,
+    stop_nodes: &[Option<NodeIndex>],
+    cell_size_degrees: f64,
+) -> Vec<Vec<RaptorStopId>> {
+    let mut cells: HashMap<(i64, i64), Vec<RaptorStopId>> = HashMap::new();
+
+    for (stop_idx, node) in stop_nodes.iter().enumerate() {
+        if node.is_none() {
+            continue;
+        }
+        let location = graph.transit_data.stops[stop_idx].geometry;
+        let cell = (
+            (location.x() / cell_size_degrees).floor() as i64,
+            (location.y() / cell_size_degrees).floor() as i64,
+        );
+        cells.entry(cell).or_default().push(stop_idx);
+    }
+
+    cells.into_values().collect()
+}
+
+/// A Dijkstra tree rooted at one street node, cached so every cluster
+/// member snapped to that same node can reuse it instead of re-searching.
+struct Tree {
+    weights: HashMap<NodeIndex, Time>,
+    paths: HashMap<NodeIndex, WalkingPath>,
+}
+
+impl Tree {
+    fn rooted_at(graph: &TransitModel, source_node: NodeIndex, max_transfer_time: Time) -> Self {
+        Self {
+            weights: dijkstra::dijkstra_path_weights(
+                &graph.street_graph,
+                source_node,
+                None,
+                Some(f64::from(max_transfer_time)),
+            ),
+            paths: dijkstra::dijkstra_paths(
+                &graph.street_graph,
+                source_node,
+                None,
+                Some(f64::from(max_transfer_time)),
+            ),
+        }
+    }
+}
+
+/// Derives a footpath from every cluster member to every stop within
+/// `max_length` meters. Members that snap to the same street node share one
+/// [`Tree`] rooted at that node — the tree's distances are exact regardless
+/// of which member asked for it, since they all start from the identical
+/// node. Members with a distinct snapped node get their own tree instead of
+/// an adjustment from a neighbor's, because there is no general way to
+/// derive one node's shortest-path distances from another's tree without
+/// risking an understated or overstated duration.
+fn footpaths_for_cluster(
+    graph: &TransitModel,
+    stop_nodes: &[Option<NodeIndex>],
+    cluster: &[RaptorStopId],
+    max_transfer_time: Time,
+    max_length: f64,
+) -> Vec<((RaptorStopId, RaptorStopId), Footpath)> {
+    let mut footpaths = Vec::new();
+    let mut trees: HashMap<NodeIndex, Tree> = HashMap::new();
+
+    for &source_idx in cluster {
+        let Some(source_node) = stop_nodes[source_idx] else {
+            continue;
+        };
+
+        let tree = trees
+            .entry(source_node)
+            .or_insert_with(|| Tree::rooted_at(graph, source_node, max_transfer_time));
+
+        let source_location = graph.transit_data.stops[source_idx].geometry;
+
+        for (target_idx, target_node) in stop_nodes.iter().enumerate() {
+            if target_idx == source_idx {
+                continue;
+            }
+            let Some(target_node) = *target_node else {
+                continue;
+            };
+            let Some(&duration) = tree.weights.get(&target_node) else {
+                continue;
+            };
+            let Some(path) = tree.paths.get(&target_node) else {
+                continue;
+            };
+
+            let target_location = graph.transit_data.stops[target_idx].geometry;
+            if haversine_distance_meters(source_location, target_location) > max_length {
+                continue;
+            }
+
+            let polyline =
+                snap_endpoints(path.nodes(), source_location.into(), target_location.into());
+            footpaths.push(((source_idx, target_idx), Footpath { duration, polyline }));
+        }
+    }
+
+    footpaths
+}
+
+/// Replaces a path's `NAN`-placeholder first/last coordinates (the
+/// convention on-demand transfer-geometry paths also use) with the
+/// requesting stop's exact location.
+fn snap_endpoints(nodes: &[Coord<f64>], source: Coord<f64>, target: Coord<f64>) -> Vec<Coord<f64>> {
+    let mut polyline = nodes.to_vec();
+    if let Some(first) = polyline.first_mut() {
+        *first = source;
+    }
+    if let Some(last) = polyline.last_mut() {
+        *last = target;
+    }
+    polyline
+}
+
+#[cfg(test)]
+mod tests {
+    use hashbrown::HashMap as HbHashMap;
+    use petgraph::graph::DiGraph;
+
+    use super::footpaths_for_cluster;
+    use crate::TransitModel;
+    use crate::model::streets::components::{StreetEdge, StreetNode};
+    use crate::model::streets::network::StreetGraph;
+    use crate::model::{PublicTransitData, Stop};
+
+    fn stop_at(stop_id: &str, geometry: geo::Point<f64>) -> Stop {
+        Stop {
+            stop_id: stop_id.to_string(),
+            geometry,
+            routes_start: 0,
+            routes_len: 0,
+            transfers_start: 0,
+            transfers_len: 0,
+            wheelchair_boarding: None,
+            min_change_time: None,
+        }
+    }
+
+    /// Three stops in a line (`a -- b -- c`), all in the same cluster, with
+    /// `a` as the representative. `b` is one edge closer to `c` than `a` is,
+    /// so if `footpaths_for_cluster` ever went back to reusing the
+    /// representative's distances for every member, `b`'s footpath to `c`
+    /// would incorrectly report `a`'s (longer) duration instead of its own.
+    #[test]
+    fn non_representative_member_gets_its_own_duration() {
+        let mut graph = DiGraph::<StreetNode, StreetEdge>::new();
+        let node_a = graph.add_node(StreetNode {
+            id: osm4routing::NodeId(0),
+            geometry: geo::Point::new(0.0, 0.0),
+        });
+        let node_b = graph.add_node(StreetNode {
+            id: osm4routing::NodeId(1),
+            geometry: geo::Point::new(0.0, 0.0),
+        });
+        let node_c = graph.add_node(StreetNode {
+            id: osm4routing::NodeId(2),
+            geometry: geo::Point::new(0.0, 0.0),
+        });
+
+        for &(from, to, weight) in &[(node_a, node_b, 100), (node_b, node_c, 100)] {
+            let edge = StreetEdge {
+                weight,
+                geometry: geo::LineString(vec![]),
+            };
+            graph.add_edge(from, to, edge.clone());
+            graph.add_edge(to, from, edge);
+        }
+
+        let street_graph = StreetGraph { graph };
+        let transit_data = PublicTransitData {
+            routes: Vec::new(),
+            route_stops: Vec::new(),
+            stop_times: Vec::new(),
+            stops: vec![
+                stop_at("a", geo::Point::new(0.0, 0.0)),
+                stop_at("b", geo::Point::new(0.0, 0.0)),
+                stop_at("c", geo::Point::new(0.0, 0.0)),
+            ],
+            stop_routes: Vec::new(),
+            transfers: Vec::new(),
+            node_to_stop: HbHashMap::new(),
+            feeds_meta: Vec::new(),
+            gtfs_transfers: Vec::new(),
+            stop_id_index: HbHashMap::new(),
+            route_id_index: HbHashMap::new(),
+            trip_id_index: HbHashMap::new(),
+            shapes: HbHashMap::new(),
+            footpaths: HbHashMap::new(),
+        };
+        let model = TransitModel {
+            street_graph,
+            transit_data,
+            contraction_hierarchy: None,
+        };
+
+        let stop_nodes = vec![Some(node_a), Some(node_b), Some(node_c)];
+        let cluster = vec![0, 1, 2];
+
+        let footpaths = footpaths_for_cluster(&model, &stop_nodes, &cluster, 10_000, f64::MAX);
+
+        let from_a_to_c = footpaths
+            .iter()
+            .find(|((from, to), _)| *from == 0 && *to == 2)
+            .expect("a reaches c")
+            .1
+            .duration;
+        let from_b_to_c = footpaths
+            .iter()
+            .find(|((from, to), _)| *from == 1 && *to == 2)
+            .expect("b reaches c")
+            .1
+            .duration;
+
+        assert_eq!(from_a_to_c, 200);
+        assert_eq!(from_b_to_c, 100);
+        assert_ne!(from_b_to_c, from_a_to_c);
+    }
+
+    /// Two stops (`a` and `a2`) snapped to the same street node, alongside a
+    /// third stop `c` two edges away. Both should report the identical
+    /// duration to `c`, since they share the exact same Dijkstra tree.
+    #[test]
+    fn members_sharing_a_node_get_identical_durations() {
+        let mut graph = DiGraph::<StreetNode, StreetEdge>::new();
+        let node_a = graph.add_node(StreetNode {
+            id: osm4routing::NodeId(0),
+            geometry: geo::Point::new(0.0, 0.0),
+        });
+        let node_b = graph.add_node(StreetNode {
+            id: osm4routing::NodeId(1),
+            geometry: geo::Point::new(0.0, 0.0),
+        });
+        let node_c = graph.add_node(StreetNode {
+            id: osm4routing::NodeId(2),
+            geometry: geo::Point::new(0.0, 0.0),
+        });
+
+        for &(from, to, weight) in &[(node_a, node_b, 100), (node_b, node_c, 100)] {
+            let edge = StreetEdge {
+                weight,
+                geometry: geo::LineString(vec![]),
+            };
+            graph.add_edge(from, to, edge.clone());
+            graph.add_edge(to, from, edge);
+        }
+
+        let street_graph = StreetGraph { graph };
+        let transit_data = PublicTransitData {
+            routes: Vec::new(),
+            route_stops: Vec::new(),
+            stop_times: Vec::new(),
+            stops: vec![
+                stop_at("a", geo::Point::new(0.0, 0.0)),
+                stop_at("a2", geo::Point::new(0.0, 0.0)),
+                stop_at("c", geo::Point::new(0.0, 0.0)),
+            ],
+            stop_routes: Vec::new(),
+            transfers: Vec::new(),
+            node_to_stop: HbHashMap::new(),
+            feeds_meta: Vec::new(),
+            gtfs_transfers: Vec::new(),
+            stop_id_index: HbHashMap::new(),
+            route_id_index: HbHashMap::new(),
+            trip_id_index: HbHashMap::new(),
+            shapes: HbHashMap::new(),
+            footpaths: HbHashMap::new(),
+        };
+        let model = TransitModel {
+            street_graph,
+            transit_data,
+            contraction_hierarchy: None,
+        };
+
+        // `a` and `a2` are different stops that both snap to `node_a`.
+        let stop_nodes = vec![Some(node_a), Some(node_a), Some(node_c)];
+        let cluster = vec![0, 1, 2];
+
+        let footpaths = footpaths_for_cluster(&model, &stop_nodes, &cluster, 10_000, f64::MAX);
+
+        let from_a_to_c = footpaths
+            .iter()
+            .find(|((from, to), _)| *from == 0 && *to == 2)
+            .expect("a reaches c")
+            .1
+            .duration;
+        let from_a2_to_c = footpaths
+            .iter()
+            .find(|((from, to), _)| *from == 1 && *to == 2)
+            .expect("a2 reaches c")
+            .1
+            .duration;
+
+        assert_eq!(from_a_to_c, 200);
+        assert_eq!(from_a2_to_c, 200);
+    }
+}
@@ -6,4 +6,6 @@ mod raw_types;
 
 pub use parser::deserialize_gtfs_file;
 pub use processor::transit_model_from_gtfs;
-pub use raw_types::{FeedInfo, FeedRoute, FeedStop, FeedStopTime, FeedTrip};
+pub use raw_types::{
+    FeedFrequency, FeedInfo, FeedRoute, FeedStop, FeedStopTime, FeedTransfer, FeedTrip,
+};
@@ -1,14 +1,21 @@
+use chrono::{Datelike, NaiveDate, Weekday};
 use geo::Point;
 use hashbrown::{HashMap, HashSet};
 use log::warn;
 
 use super::{
     parser::{deserialize_gtfs_file, parse_time},
-    raw_types::{FeedInfo, FeedRoute, FeedService, FeedStop, FeedStopTime, FeedTrip},
+    raw_types::{
+        FeedCalendarDates, FeedFrequency, FeedInfo, FeedRoute, FeedService, FeedShape, FeedStop,
+        FeedStopTime, FeedTransfer, FeedTrip,
+    },
 };
 use crate::{
     Error,
-    model::{PublicTransitData, RaptorStopId, Route, RouteId, Stop, StopTime},
+    model::{
+        GtfsRouteId, GtfsStopId, GtfsTripId, PublicTransitData, RaptorStopId, Route, RouteId,
+        Shape, Stop, StopTime, Time,
+    },
 };
 use crate::{loading::config::TransitModelConfig, model::transit::types::FeedMeta};
 
@@ -18,14 +25,38 @@ use crate::{loading::config::TransitModelConfig, model::transit::types::FeedMeta
 ///
 /// If a `stop_sequence` cannot be parsed as a u32
 pub fn transit_model_from_gtfs(config: &TransitModelConfig) -> Result<PublicTransitData, Error> {
-    let (stops, mut trips, mut stop_times, services, feed_info_vec) = load_raw_feed(config)?;
+    let (
+        stops,
+        mut trips,
+        mut stop_times,
+        services,
+        calendar_dates,
+        gtfs_transfers,
+        frequencies,
+        feed_info_vec,
+        feed_routes,
+        feed_shapes,
+    ) = load_raw_feed(config)?;
 
+    let shapes = build_shapes(feed_shapes);
+
+    // `config.date` is also threaded onto each `FeedMeta` so callers can
+    // confirm which service day a loaded model was actually built for.
     let feeds_meta = feed_info_vec
         .into_iter()
-        .map(|info| FeedMeta { feed_info: info })
+        .map(|info| FeedMeta {
+            feed_info: info,
+            date: config.date,
+        })
         .collect::<Vec<_>>();
 
-    filter_trips_by_service_day(config, &services, &mut trips, &mut stop_times);
+    filter_trips_by_service_day(
+        config,
+        &services,
+        &calendar_dates,
+        &mut trips,
+        &mut stop_times,
+    );
 
     // Create maps for fast lookup during conversion
     let stop_id_map: HashMap<String, RaptorStopId> = stops
@@ -40,6 +71,18 @@ pub fn transit_model_from_gtfs(config: &TransitModelConfig) -> Result<PublicTran
         .map(|(idx, trip)| (trip.trip_id.as_str(), idx))
         .collect();
 
+    // `trip_id -> FeedTrip` and `route_id -> FeedRoute` lookups so each
+    // synthesized RAPTOR route can carry its GTFS route/trip metadata
+    // (short/long name, color, headsign, direction) for display purposes.
+    let trip_by_id: HashMap<&str, &FeedTrip> = trips
+        .iter()
+        .map(|trip| (trip.trip_id.as_str(), trip))
+        .collect();
+    let route_by_id: HashMap<&str, &FeedRoute> = feed_routes
+        .iter()
+        .map(|route| (route.route_id.as_str(), route))
+        .collect();
+
     // Map from trip_id to vec of stop times
     let mut trip_stop_times: HashMap<String, Vec<FeedStopTime>> = HashMap::new();
     for stop_time in stop_times {
@@ -60,6 +103,16 @@ pub fn transit_model_from_gtfs(config: &TransitModelConfig) -> Result<PublicTran
         });
     }
 
+    // Group frequencies.txt rows by trip_id so a template trip's stop_times
+    // can be expanded into one synthetic trip per headway-spaced departure.
+    let mut trip_frequencies: HashMap<&str, Vec<&FeedFrequency>> = HashMap::new();
+    for frequency in &frequencies {
+        trip_frequencies
+            .entry(frequency.trip_id.as_str())
+            .or_default()
+            .push(frequency);
+    }
+
     // Key raptor transit data model vectors
 
     let mut stop_routes: Vec<RouteId> = Vec::new();
@@ -67,8 +120,14 @@ pub fn transit_model_from_gtfs(config: &TransitModelConfig) -> Result<PublicTran
     // convert Raw GTFS data to Raptor data
     let mut stops_vec = create_stops_vector(stops);
     // Process trips
-    let (stop_times, route_stops, routes_vec) =
-        process_trip_stop_times(&stop_id_map, &trip_id_map, &trip_stop_times);
+    let (stop_times, route_stops, routes_vec) = process_trip_stop_times(
+        &stop_id_map,
+        &trip_id_map,
+        &trip_stop_times,
+        &trip_frequencies,
+        &trip_by_id,
+        &route_by_id,
+    );
     drop(trip_stop_times);
 
     // Index of routes for each stop
@@ -90,6 +149,29 @@ pub fn transit_model_from_gtfs(config: &TransitModelConfig) -> Result<PublicTran
         stop_routes.extend(routes);
     }
 
+    // Reverse lookups from GTFS string ids back to the internal integer
+    // indices, built once up front so callers can correlate routing results
+    // with the source feed without a linear scan.
+    let stop_id_index: HashMap<GtfsStopId, RaptorStopId> = stops_vec
+        .iter()
+        .enumerate()
+        .map(|(idx, stop)| (GtfsStopId(stop.stop_id.clone()), idx))
+        .collect();
+
+    let mut route_id_index: HashMap<GtfsRouteId, Vec<RouteId>> = HashMap::new();
+    for (idx, route) in routes_vec.iter().enumerate() {
+        route_id_index
+            .entry(GtfsRouteId(route.route_id.clone()))
+            .or_default()
+            .push(idx);
+    }
+
+    let trip_id_index: HashMap<GtfsTripId, RouteId> = routes_vec
+        .iter()
+        .enumerate()
+        .map(|(idx, route)| (GtfsTripId(route.trip_id.clone()), idx))
+        .collect();
+
     Ok(PublicTransitData {
         routes: routes_vec,
         route_stops,
@@ -99,38 +181,80 @@ pub fn transit_model_from_gtfs(config: &TransitModelConfig) -> Result<PublicTran
         transfers: vec![],            // Will be filled in `calculate_transfers`
         node_to_stop: HashMap::new(), // Empty node to stop mapping initially
         feeds_meta,
+        gtfs_transfers,
+        stop_id_index,
+        route_id_index,
+        trip_id_index,
+        shapes,
+        footpaths: HashMap::new(), // Will be filled by the footpath-preparation step
     })
 }
 
+/// Groups `shapes.txt` rows by `shape_id`, sorts each group by
+/// `shape_pt_sequence`, and builds a [`Shape`] (with cumulative distance)
+/// from the resulting point sequence. Rows with an unparseable sequence or
+/// coordinate are dropped rather than failing the whole shape.
+fn build_shapes(feed_shapes: Vec<FeedShape>) -> HashMap<String, Shape> {
+    let mut points_by_shape: HashMap<String, Vec<(u32, Point<f64>)>> = HashMap::new();
+
+    for shape in feed_shapes {
+        let (Ok(sequence), Ok(lat), Ok(lon)) = (
+            shape.shape_pt_sequence.parse::<u32>(),
+            shape.shape_pt_lat.parse::<f64>(),
+            shape.shape_pt_lon.parse::<f64>(),
+        ) else {
+            continue;
+        };
+        points_by_shape
+            .entry(shape.shape_id)
+            .or_default()
+            .push((sequence, Point::new(lon, lat)));
+    }
+
+    points_by_shape
+        .into_iter()
+        .map(|(shape_id, mut points)| {
+            points.sort_by_key(|&(sequence, _)| sequence);
+            let points = points.into_iter().map(|(_, point)| point).collect();
+            (shape_id, Shape::new(points))
+        })
+        .collect()
+}
+
 fn filter_trips_by_service_day(
     config: &TransitModelConfig,
     services: &[FeedService],
+    calendar_dates: &[FeedCalendarDates],
     trips: &mut Vec<FeedTrip>,
     stop_times: &mut Vec<FeedStopTime>,
 ) {
-    // Create set of service_id for the selected day of the week
-    let active_services: HashSet<&str> = services
-        .iter()
-        .filter_map(|service| {
-            let is_active = match config.day_of_week.as_str() {
-                "monday" => service.monday == "1",
-                "tuesday" => service.tuesday == "1",
-                "wednesday" => service.wednesday == "1",
-                "thursday" => service.thursday == "1",
-                "friday" => service.friday == "1",
-                "saturday" => service.saturday == "1",
-                "sunday" => service.sunday == "1",
-                _ => false,
-            };
-            if is_active {
-                Some(service.service_id.as_str())
-            } else {
-                None
-            }
-        })
-        .collect();
+    // Prefer a concrete date (honoring calendar_dates.txt exceptions) and
+    // fall back to a plain weekday match when no date was configured.
+    let active_services: HashSet<&str> = match config.date {
+        Some(date) => active_services_for_date(date, services, calendar_dates),
+        None => services
+            .iter()
+            .filter_map(|service| {
+                let is_active = match config.day_of_week.as_str() {
+                    "monday" => service.monday == "1",
+                    "tuesday" => service.tuesday == "1",
+                    "wednesday" => service.wednesday == "1",
+                    "thursday" => service.thursday == "1",
+                    "friday" => service.friday == "1",
+                    "saturday" => service.saturday == "1",
+                    "sunday" => service.sunday == "1",
+                    _ => false,
+                };
+                if is_active {
+                    Some(service.service_id.as_str())
+                } else {
+                    None
+                }
+            })
+            .collect(),
+    };
 
-    // Filter trips and respective stop_times by day of the week
+    // Filter trips and respective stop_times by the resulting service set
     trips.retain(|trip| active_services.contains(trip.service_id.as_str()));
     let active_trips = trips
         .iter()
@@ -139,43 +263,208 @@ fn filter_trips_by_service_day(
     stop_times.retain(|stop_time| active_trips.contains(stop_time.trip_id.as_str()));
 }
 
+/// Computes the set of `service_id`s active on `date`: every `calendar.txt`
+/// row whose date range covers `date` and whose weekday bit for `date` is
+/// set, with `calendar_dates.txt` exceptions then applied on top
+/// (`exception_type == 1` adds a service, `2` removes it).
+fn active_services_for_date<'a>(
+    date: NaiveDate,
+    services: &'a [FeedService],
+    calendar_dates: &'a [FeedCalendarDates],
+) -> HashSet<&'a str> {
+    let mut active: HashSet<&str> = services
+        .iter()
+        .filter(|service| {
+            let (Some(start), Some(end)) = (
+                parse_gtfs_date(&service.start_date),
+                parse_gtfs_date(&service.end_date),
+            ) else {
+                return false;
+            };
+            start <= date && date <= end && service_active_on_weekday(service, date.weekday())
+        })
+        .map(|service| service.service_id.as_str())
+        .collect();
+
+    for exception in calendar_dates {
+        if exception.date != Some(date) {
+            continue;
+        }
+        match exception.exception_type.as_str() {
+            "1" => {
+                active.insert(exception.service_id.as_str());
+            }
+            "2" => {
+                active.remove(exception.service_id.as_str());
+            }
+            _ => {}
+        }
+    }
+
+    active
+}
+
+fn service_active_on_weekday(service: &FeedService, weekday: Weekday) -> bool {
+    match weekday {
+        Weekday::Mon => service.monday == "1",
+        Weekday::Tue => service.tuesday == "1",
+        Weekday::Wed => service.wednesday == "1",
+        Weekday::Thu => service.thursday == "1",
+        Weekday::Fri => service.friday == "1",
+        Weekday::Sat => service.saturday == "1",
+        Weekday::Sun => service.sunday == "1",
+    }
+}
+
+fn parse_gtfs_date(date_str: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date_str, "%Y%m%d").ok()
+}
+
 fn process_trip_stop_times(
     stop_id_map: &HashMap<String, usize>,
     trip_id_map: &HashMap<&str, usize>,
     trip_stop_times: &HashMap<String, Vec<FeedStopTime>>,
+    trip_frequencies: &HashMap<&str, Vec<&FeedFrequency>>,
+    trip_by_id: &HashMap<&str, &FeedTrip>,
+    route_by_id: &HashMap<&str, &FeedRoute>,
 ) -> (Vec<StopTime>, Vec<usize>, Vec<Route>) {
     let mut stop_times_vec = Vec::new();
     let mut route_stops = Vec::new();
     let mut routes_vec = Vec::new();
 
     for (trip_id, stop_list) in trip_stop_times {
+        if trip_id_map.get(trip_id.as_str()).is_none() {
+            continue;
+        }
+
+        let parsed_stops: Vec<(usize, Time, Time)> = stop_list
+            .iter()
+            .filter_map(|stop_time| {
+                stop_id_map.get(&stop_time.stop_id).map(|&stop_idx| {
+                    (
+                        stop_idx,
+                        parse_time(&stop_time.arrival_time),
+                        parse_time(&stop_time.departure_time),
+                    )
+                })
+            })
+            .collect();
+
+        if parsed_stops.is_empty() {
+            continue;
+        }
+
         let stops_start = route_stops.len();
         let trips_start = stop_times_vec.len();
-        let num_stops = stop_list.len();
-
-        for stop_time in stop_list {
-            if let Some(&stop_idx) = stop_id_map.get(&stop_time.stop_id) {
-                route_stops.push(stop_idx);
-                stop_times_vec.push(StopTime {
-                    arrival: parse_time(&stop_time.arrival_time),
-                    departure: parse_time(&stop_time.departure_time),
-                });
-            }
-        }
+        let num_stops = parsed_stops.len();
 
-        if let Some(&_route_idx) = trip_id_map.get(trip_id.as_str()) {
-            routes_vec.push(Route {
-                num_trips: 1,
-                num_stops,
-                stops_start,
-                trips_start,
-            });
+        for &(stop_idx, _, _) in &parsed_stops {
+            route_stops.push(stop_idx);
         }
+
+        // A trip named in frequencies.txt is a template: its own stop_times
+        // give the stop sequence and relative offsets, and each configured
+        // headway window is expanded into one synthetic trip per departure
+        // instead of the single trip `stop_times.txt` would otherwise give.
+        let num_trips = match trip_frequencies.get(trip_id.as_str()) {
+            Some(freqs) => {
+                let template_start = parsed_stops[0].1;
+                let mut departures: Vec<Time> = freqs
+                    .iter()
+                    .flat_map(|frequency| expand_frequency_departures(frequency))
+                    .collect();
+                departures.sort_unstable();
+
+                for departure in &departures {
+                    let shift = i64::from(*departure) - i64::from(template_start);
+                    for &(_, arrival, departure) in &parsed_stops {
+                        stop_times_vec.push(StopTime {
+                            arrival: (i64::from(arrival) + shift) as Time,
+                            departure: (i64::from(departure) + shift) as Time,
+                        });
+                    }
+                }
+
+                departures.len()
+            }
+            None => {
+                for &(_, arrival, departure) in &parsed_stops {
+                    stop_times_vec.push(StopTime { arrival, departure });
+                }
+                1
+            }
+        };
+
+        // Each synthesized RAPTOR route corresponds to exactly one GTFS
+        // trip_id, so the trip's headsign/direction and its route's
+        // name/color are constant across every one of its `num_trips`
+        // departures (including the ones synthesized from frequencies.txt).
+        let feed_trip = trip_by_id.get(trip_id.as_str()).copied();
+        let feed_route =
+            feed_trip.and_then(|trip| route_by_id.get(trip.route_id.as_str()).copied());
+
+        routes_vec.push(Route {
+            num_trips,
+            num_stops,
+            stops_start,
+            trips_start,
+            trip_id: trip_id.clone(),
+            route_id: feed_trip.map_or_else(String::new, |trip| trip.route_id.clone()),
+            route_short_name: feed_route
+                .map_or_else(String::new, |route| route.route_short_name.clone()),
+            route_long_name: feed_route
+                .map_or_else(String::new, |route| route.route_long_name.clone()),
+            route_color: feed_route.and_then(|route| {
+                (!route.route_color.is_empty()).then(|| route.route_color.clone())
+            }),
+            trip_headsign: feed_trip.map_or_else(String::new, |trip| trip.trip_headsign.clone()),
+            direction_id: feed_trip.and_then(|trip| parse_direction_id(&trip.direction_id)),
+            wheelchair_accessible: feed_trip
+                .and_then(|trip| parse_wheelchair_flag(&trip.wheelchair_accessible)),
+            shape_id: feed_trip
+                .and_then(|trip| (!trip.shape_id.is_empty()).then(|| trip.shape_id.clone())),
+        });
     }
 
     (stop_times_vec, route_stops, routes_vec)
 }
 
+/// Parses a GTFS `direction_id` ("0", "1" or empty) into `Some(0)`,
+/// `Some(1)` or `None`.
+fn parse_direction_id(direction_id: &str) -> Option<u8> {
+    match direction_id {
+        "0" => Some(0),
+        "1" => Some(1),
+        _ => None,
+    }
+}
+
+/// Departure times for one `frequencies.txt` window: `start_time`,
+/// `start_time + headway_secs`, … while strictly less than `end_time`.
+/// `exact_times` doesn't change this generation (both exact and
+/// approximate schedules still depart on the headway), it only tells
+/// downstream consumers whether those departures are a guarantee or an
+/// average, so it isn't consulted here.
+fn expand_frequency_departures(frequency: &FeedFrequency) -> Vec<Time> {
+    let start = parse_time(&frequency.start_time);
+    let end = parse_time(&frequency.end_time);
+    let Ok(headway) = frequency.headway_secs.parse::<Time>() else {
+        return Vec::new();
+    };
+    if headway == 0 {
+        return Vec::new();
+    }
+
+    let mut departures = Vec::new();
+    let mut departure = start;
+    while departure < end {
+        departures.push(departure);
+        departure += headway;
+    }
+
+    departures
+}
+
 fn create_stops_vector(stops: Vec<FeedStop>) -> Vec<Stop> {
     let stops_vec: Vec<Stop> = stops
         .into_iter()
@@ -198,18 +487,39 @@ fn create_stops_vector(stops: Vec<FeedStop>) -> Vec<Stop> {
                 routes_len: 0,
                 transfers_start: 0,
                 transfers_len: 0,
+                wheelchair_boarding: parse_wheelchair_flag(&feed_stop.wheelchair_boarding),
+                // GTFS has no standard field for this; stops that need a
+                // longer platform-change buffer than the query's default
+                // are expected to be patched in after loading.
+                min_change_time: None,
             }
         })
         .collect();
     stops_vec
 }
 
+/// Parses a GTFS wheelchair accessibility hint (`stops.wheelchair_boarding`
+/// or `trips.wheelchair_accessible`): `"1"` is accessible, `"2"` is not, and
+/// `"0"`/empty/anything else is "no information" (`None`).
+fn parse_wheelchair_flag(flag: &str) -> Option<u8> {
+    match flag {
+        "1" => Some(1),
+        "2" => Some(2),
+        _ => None,
+    }
+}
+
 type RawGTFSmodel = (
     Vec<FeedStop>,
     Vec<FeedTrip>,
     Vec<FeedStopTime>,
     Vec<FeedService>,
+    Vec<FeedCalendarDates>,
+    Vec<FeedTransfer>,
+    Vec<FeedFrequency>,
     Vec<FeedInfo>,
+    Vec<FeedRoute>,
+    Vec<FeedShape>,
 );
 
 fn load_raw_feed(config: &TransitModelConfig) -> Result<RawGTFSmodel, Error> {
@@ -218,13 +528,32 @@ fn load_raw_feed(config: &TransitModelConfig) -> Result<RawGTFSmodel, Error> {
     let mut trips: Vec<FeedTrip> = Vec::new();
     let mut stop_times: Vec<FeedStopTime> = Vec::new();
     let mut services: Vec<FeedService> = Vec::new();
+    let mut calendar_dates: Vec<FeedCalendarDates> = Vec::new();
+    let mut transfers: Vec<FeedTransfer> = Vec::new();
+    let mut frequencies: Vec<FeedFrequency> = Vec::new();
     let mut feed_info_vec: Vec<FeedInfo> = Vec::new();
+    let mut shapes: Vec<FeedShape> = Vec::new();
     for dir in &config.gtfs_dirs {
         stops.extend(deserialize_gtfs_file(&dir.join("stops.txt"))?);
         routes.extend(deserialize_gtfs_file(&dir.join("routes.txt"))?);
         trips.extend(deserialize_gtfs_file(&dir.join("trips.txt"))?);
         stop_times.extend(deserialize_gtfs_file(&dir.join("stop_times.txt"))?);
         services.extend(deserialize_gtfs_file(&dir.join("calendar.txt"))?);
+        // calendar_dates.txt, transfers.txt, frequencies.txt and shapes.txt
+        // are all optional in GTFS; an absent file contributes none rather
+        // than failing the whole feed.
+        if let Ok(feed_calendar_dates) = deserialize_gtfs_file(&dir.join("calendar_dates.txt")) {
+            calendar_dates.extend(feed_calendar_dates);
+        }
+        if let Ok(feed_transfers) = deserialize_gtfs_file(&dir.join("transfers.txt")) {
+            transfers.extend(feed_transfers);
+        }
+        if let Ok(feed_frequencies) = deserialize_gtfs_file(&dir.join("frequencies.txt")) {
+            frequencies.extend(feed_frequencies);
+        }
+        if let Ok(feed_shapes) = deserialize_gtfs_file(&dir.join("shapes.txt")) {
+            shapes.extend(feed_shapes);
+        }
         feed_info_vec.extend(deserialize_gtfs_file(&dir.join("feed_info.txt"))?);
     }
     stops.shrink_to_fit();
@@ -232,5 +561,20 @@ fn load_raw_feed(config: &TransitModelConfig) -> Result<RawGTFSmodel, Error> {
     trips.shrink_to_fit();
     stop_times.shrink_to_fit();
     services.shrink_to_fit();
-    Ok((stops, trips, stop_times, services, feed_info_vec))
+    calendar_dates.shrink_to_fit();
+    transfers.shrink_to_fit();
+    frequencies.shrink_to_fit();
+    shapes.shrink_to_fit();
+    Ok((
+        stops,
+        trips,
+        stop_times,
+        services,
+        calendar_dates,
+        transfers,
+        frequencies,
+        feed_info_vec,
+        routes,
+        shapes,
+    ))
 }
@@ -1,5 +1,7 @@
 use serde::Deserialize;
 
+use crate::Time;
+
 #[derive(Debug, Deserialize, Default)]
 #[serde(default)]
 pub struct FeedCalendar {
@@ -112,7 +114,7 @@ pub struct FeedCalendarDates {
     pub exception_type: String,
 }
 
-#[derive(Debug, Deserialize, Default, Clone)]
+#[derive(Debug, Deserialize, serde::Serialize, Default, Clone)]
 #[serde(default)]
 #[allow(clippy::struct_field_names)]
 pub struct FeedInfo {
@@ -126,6 +128,50 @@ pub struct FeedInfo {
     pub feed_version: String,
 }
 
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct FeedShape {
+    pub shape_id: String,
+    pub shape_pt_lat: String,
+    pub shape_pt_lon: String,
+    pub shape_pt_sequence: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct FeedFrequency {
+    pub trip_id: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub headway_secs: String,
+    pub exact_times: String,
+}
+
+#[derive(Debug, Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct FeedTransfer {
+    pub from_stop_id: String,
+    pub to_stop_id: String,
+    pub transfer_type: u8,
+    #[serde(deserialize_with = "deserialize_optional_time")]
+    pub min_transfer_time: Option<Time>,
+}
+
+fn deserialize_optional_time<'de, D>(deserializer: D) -> Result<Option<Time>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let time_str = String::deserialize(deserializer)?;
+    if time_str.is_empty() {
+        Ok(None)
+    } else {
+        time_str
+            .parse::<Time>()
+            .map(Some)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 fn deserialize_gtfs_date<'de, D>(deserializer: D) -> Result<Option<chrono::NaiveDate>, D::Error>
 where
     D: serde::Deserializer<'de>,
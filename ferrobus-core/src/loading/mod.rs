@@ -3,9 +3,14 @@
 
 mod builder;
 mod config;
+mod footpaths;
 pub mod gtfs;
 pub mod osm;
 mod transfers;
 
 pub use builder::create_transit_model;
-pub use config::TransitModelConfig;
+pub use config::{
+    NeedTransfer, TransitModelConfig, allow_all_transfers, disjoint_route_transfers,
+    max_distance_transfers,
+};
+pub use gtfs::FeedTransfer;
@@ -1,14 +1,21 @@
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use log::{info, warn};
 use petgraph::graph::NodeIndex;
 use rayon::prelude::*;
 
-use crate::{RaptorStopId, Time, TransitModel, model::Transfer, routing::dijkstra};
+use super::config::{NeedTransfer, TransitModelConfig};
+use crate::{RaptorStopId, Time, TransitModel, model::Footpath, model::Transfer};
 
-/// Calculate transfers between stops using the street network
-/// Merges with GTFS-defined transfers (GTFS takes priority)
-pub(crate) fn calculate_transfers(graph: &mut TransitModel) {
+/// Calculate transfers between stops using the precomputed footpath cache
+/// (see [`super::footpaths::precompute_footpaths`]). Merges with
+/// GTFS-defined transfers (GTFS takes priority).
+pub(crate) fn calculate_transfers(
+    graph: &mut TransitModel,
+    config: &TransitModelConfig,
+    footpaths: &HashMap<(RaptorStopId, RaptorStopId), Footpath>,
+) {
     let max_transfer_time = graph.meta.max_transfer_time;
+    let need_transfer = config.need_transfer.as_deref();
     let stop_count = graph.transit_data.stops.len();
 
     info!("Calculating transfers between {stop_count} stops");
@@ -16,7 +23,13 @@ pub(crate) fn calculate_transfers(graph: &mut TransitModel) {
     // Snap all transit stops to street network nodes (Some = snapped, None = too far)
     let stop_nodes = snap_stops_to_network(graph);
     // Calculate transfers for all stops that could be snapped
-    let computed_transfers = calculate_stop_transfers(graph, &stop_nodes, max_transfer_time);
+    let computed_transfers = calculate_stop_transfers(
+        graph,
+        &stop_nodes,
+        footpaths,
+        max_transfer_time,
+        need_transfer,
+    );
 
     let gtfs_transfers_raw = std::mem::take(&mut graph.transit_data.gtfs_transfers);
 
@@ -29,42 +42,45 @@ pub(crate) fn calculate_transfers(graph: &mut TransitModel) {
         .collect();
 
     // Convert GTFS transfers to internal format
-    let gtfs_transfers =
+    let (gtfs_transfers, blocked_transfers) =
         convert_gtfs_transfers_to_internal(&gtfs_transfers_raw, &stop_id_map, max_transfer_time);
 
     if !gtfs_transfers.is_empty() {
         let gtfs_count: usize = gtfs_transfers.iter().map(|(_, t)| t.len()).sum();
         info!("Loaded {gtfs_count} GTFS-defined transfers");
     }
+    if !blocked_transfers.is_empty() {
+        info!(
+            "{} GTFS transfer(s) marked not-possible (transfer_type 3)",
+            blocked_transfers.len()
+        );
+    }
 
-    // GTFS overrides computed
-    let merged_transfers = merge_transfers(computed_transfers, gtfs_transfers);
+    // GTFS overrides computed, and type-3 pairs block the computed footpath outright
+    let merged_transfers = merge_transfers(computed_transfers, gtfs_transfers, &blocked_transfers);
 
     update_transit_model_with_transfers(graph, merged_transfers, &stop_nodes);
 }
 
+/// Converts `transfers.txt` rows into the internal per-stop transfer format,
+/// alongside the set of `(from_stop, to_stop)` pairs GTFS marks as
+/// `transfer_type == 3` ("transfers are not possible between routes at this
+/// location" — see <https://gtfs.org/documentation/schedule/reference/#transferstxt>).
+/// Those pairs carry no duration of their own; they are a veto [`merge_transfers`]
+/// must apply against any computed footpath for the same pair, not merely an
+/// entry to skip.
 fn convert_gtfs_transfers_to_internal(
     gtfs_transfers: &[crate::loading::FeedTransfer],
     stop_id_map: &HashMap<String, RaptorStopId>,
     max_transfer_time: Time,
-) -> Vec<(RaptorStopId, Vec<Transfer>)> {
+) -> (
+    Vec<(RaptorStopId, Vec<Transfer>)>,
+    HashSet<(RaptorStopId, RaptorStopId)>,
+) {
     let mut transfers_by_stop: HashMap<RaptorStopId, Vec<Transfer>> = HashMap::new();
+    let mut blocked: HashSet<(RaptorStopId, RaptorStopId)> = HashSet::new();
 
     for transfer in gtfs_transfers {
-        // "Transfers are not possible between routes at the location" link
-        // https://gtfs.org/documentation/schedule/reference/#transferstxt
-        if transfer.transfer_type == 3 {
-            continue;
-        }
-
-        let Some(duration) = transfer.min_transfer_time else {
-            continue;
-        };
-
-        if duration > max_transfer_time {
-            continue;
-        }
-
         // GTFS Stop IDs to internal indices of raptor flat model
         let Some(&from_idx) = stop_id_map.get(&transfer.from_stop_id) else {
             warn!(
@@ -86,6 +102,19 @@ fn convert_gtfs_transfers_to_internal(
             continue;
         }
 
+        if transfer.transfer_type == 3 {
+            blocked.insert((from_idx, to_idx));
+            continue;
+        }
+
+        let Some(duration) = transfer.min_transfer_time else {
+            continue;
+        };
+
+        if duration > max_transfer_time {
+            continue;
+        }
+
         transfers_by_stop
             .entry(from_idx)
             .or_default()
@@ -95,12 +124,13 @@ fn convert_gtfs_transfers_to_internal(
             });
     }
 
-    transfers_by_stop.into_iter().collect()
+    (transfers_by_stop.into_iter().collect(), blocked)
 }
 
 fn merge_transfers(
     computed: Vec<(RaptorStopId, Vec<Transfer>)>,
     gtfs: Vec<(RaptorStopId, Vec<Transfer>)>,
+    blocked: &HashSet<(RaptorStopId, RaptorStopId)>,
 ) -> Vec<(RaptorStopId, Vec<Transfer>)> {
     // from_stop, to_stop
     let mut merged: HashMap<RaptorStopId, HashMap<RaptorStopId, Transfer>> = HashMap::new();
@@ -120,6 +150,14 @@ fn merge_transfers(
         }
     }
 
+    // transfer_type == 3 blocks the pair outright, even if a computed
+    // footpath (or another GTFS row) would otherwise have produced one.
+    for &(from_stop, to_stop) in blocked {
+        if let Some(entry) = merged.get_mut(&from_stop) {
+            entry.remove(&to_stop);
+        }
+    }
+
     merged
         .into_iter()
         .map(|(from_stop, transfers_map)| {
@@ -132,7 +170,7 @@ fn merge_transfers(
 
 /// Snap transit stops to their nearest street network nodes
 /// Returns None for stops that are too far from any street (> max_transfer_time walking distance)
-fn snap_stops_to_network(graph: &TransitModel) -> Vec<Option<NodeIndex>> {
+pub(crate) fn snap_stops_to_network(graph: &TransitModel) -> Vec<Option<NodeIndex>> {
     let max_snap_distance = graph.meta.max_transfer_time;
 
     graph
@@ -162,20 +200,23 @@ fn snap_stops_to_network(graph: &TransitModel) -> Vec<Option<NodeIndex>> {
 fn calculate_stop_transfers(
     graph: &TransitModel,
     stop_nodes: &[Option<NodeIndex>],
+    footpaths: &HashMap<(RaptorStopId, RaptorStopId), Footpath>,
     max_transfer_time: Time,
+    need_transfer: Option<&NeedTransfer>,
 ) -> Vec<(RaptorStopId, Vec<Transfer>)> {
     (0..stop_nodes.len())
         .into_par_iter()
         .filter_map(|source_idx| {
             // Skip stops that couldn't be snapped to streets
-            let source_node = stop_nodes[source_idx]?;
+            stop_nodes[source_idx]?;
 
             let transfers = find_transfers_from_stop(
                 graph,
                 stop_nodes,
+                footpaths,
                 source_idx,
-                source_node,
                 max_transfer_time,
+                need_transfer,
             );
 
             if transfers.is_empty() {
@@ -187,42 +228,43 @@ fn calculate_stop_transfers(
         .collect()
 }
 
-/// Find all valid transfers from a single stop
+/// Find all valid transfers from a single stop, reading walking times from
+/// the precomputed footpath cache instead of running a street search.
+///
+/// `need_transfer`, if set, is consulted for each candidate target stop and
+/// can veto an otherwise-reachable transfer (e.g. to avoid generating a
+/// footpath between stops that already share a route); `None` keeps every
+/// transfer within `max_transfer_time`.
 fn find_transfers_from_stop(
     graph: &TransitModel,
     stop_nodes: &[Option<NodeIndex>],
+    footpaths: &HashMap<(RaptorStopId, RaptorStopId), Footpath>,
     source_idx: usize,
-    source_node: NodeIndex,
     max_transfer_time: Time,
+    need_transfer: Option<&NeedTransfer>,
 ) -> Vec<Transfer> {
-    // Get reachable nodes within time limit
-    let reachable = dijkstra::dijkstra_path_weights(
-        &graph.street_graph,
-        source_node,
-        None,
-        Some(f64::from(max_transfer_time)),
-    );
-
     stop_nodes
         .iter()
         .enumerate()
         .filter_map(|(target_idx, target_node_opt)| {
-            // Skip self-transfers
-            if source_idx == target_idx {
+            // Skip self-transfers and stops that couldn't be snapped to streets
+            if source_idx == target_idx || target_node_opt.is_none() {
                 return None;
             }
 
-            // Skip stops that couldn't be snapped to streets
-            let target_node = (*target_node_opt)?;
-
             // Check if target is reachable within time limit
-            reachable
-                .get(&target_node)
-                .filter(|&&time| time <= max_transfer_time)
-                .map(|&time| Transfer {
-                    target_stop: target_idx,
-                    duration: time,
-                })
+            let footpath = footpaths
+                .get(&(source_idx, target_idx))
+                .filter(|footpath| footpath.duration <= max_transfer_time)?;
+
+            if !need_transfer.is_none_or(|predicate| predicate(graph, source_idx, target_idx)) {
+                return None;
+            }
+
+            Some(Transfer {
+                target_stop: target_idx,
+                duration: footpath.duration,
+            })
         })
         .collect()
 }
@@ -262,3 +304,89 @@ fn update_transit_model_with_transfers(
     // Store all transfers
     graph.transit_data.transfers = all_transfers;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loading::FeedTransfer;
+
+    fn feed_transfer(from: &str, to: &str, transfer_type: u8, min_transfer_time: Option<Time>) -> FeedTransfer {
+        FeedTransfer {
+            from_stop_id: from.to_string(),
+            to_stop_id: to.to_string(),
+            transfer_type,
+            min_transfer_time,
+        }
+    }
+
+    fn stop_id_map() -> HashMap<String, RaptorStopId> {
+        [("a".to_string(), 0), ("b".to_string(), 1), ("c".to_string(), 2)]
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn type_3_transfer_is_blocked_not_merged() {
+        let gtfs = vec![feed_transfer("a", "b", 3, None)];
+        let (transfers, blocked) = convert_gtfs_transfers_to_internal(&gtfs, &stop_id_map(), 600);
+
+        assert!(transfers.is_empty());
+        assert_eq!(blocked.into_iter().collect::<Vec<_>>(), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn normal_transfer_is_kept() {
+        let gtfs = vec![feed_transfer("a", "b", 0, Some(120))];
+        let (transfers, blocked) = convert_gtfs_transfers_to_internal(&gtfs, &stop_id_map(), 600);
+
+        assert!(blocked.is_empty());
+        assert_eq!(transfers.len(), 1);
+        let (from_stop, stop_transfers) = &transfers[0];
+        assert_eq!(*from_stop, 0);
+        assert_eq!(stop_transfers[0].target_stop, 1);
+        assert_eq!(stop_transfers[0].duration, 120);
+    }
+
+    /// A type-3 row must remove an already-computed footpath for the same
+    /// pair, not merely decline to add its own entry.
+    #[test]
+    fn merge_transfers_blocks_computed_footpath() {
+        let computed = vec![(
+            0,
+            vec![Transfer {
+                target_stop: 1,
+                duration: 90,
+            }],
+        )];
+        let blocked = HashSet::from([(0, 1)]);
+
+        let merged = merge_transfers(computed, Vec::new(), &blocked);
+
+        assert!(merged.is_empty(), "blocked pair must not survive merging");
+    }
+
+    #[test]
+    fn merge_transfers_gtfs_overrides_computed() {
+        let computed = vec![(
+            0,
+            vec![Transfer {
+                target_stop: 1,
+                duration: 90,
+            }],
+        )];
+        let gtfs = vec![(
+            0,
+            vec![Transfer {
+                target_stop: 1,
+                duration: 30,
+            }],
+        )];
+
+        let merged = merge_transfers(computed, gtfs, &HashSet::new());
+
+        assert_eq!(merged.len(), 1);
+        let (from_stop, transfers) = &merged[0];
+        assert_eq!(*from_stop, 0);
+        assert_eq!(transfers[0].duration, 30);
+    }
+}
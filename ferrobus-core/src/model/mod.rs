@@ -11,6 +11,11 @@ pub mod transit_model;
 pub use transit_model::{TransitModel, TransitPoint};
 
 // Re-export of basic types for convenience
+pub use streets::contraction::ContractionHierarchy;
 pub use streets::network::StreetGraph;
-pub use transit::data::PublicTransitData;
+pub use transit::data::{
+    Departure, Footpath, GtfsRouteId, GtfsStopId, GtfsTripId, HeadsignGroup, PublicTransitData,
+    RouteGroup,
+};
+pub use transit::shapes::Shape;
 pub use transit::types::{RaptorStopId, Route, RouteId, Stop, StopTime, Time};
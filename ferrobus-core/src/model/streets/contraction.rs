@@ -0,0 +1,357 @@
+//! Contraction hierarchies: a one-time preprocessing pass over the street
+//! graph that lets repeated point-to-point queries (transfer-geometry
+//! reconstruction, access/egress legs) skip re-expanding the whole network
+//! every time.
+//!
+//! Nodes are contracted one by one, in an order that approximates "least
+//! important first" by current degree. Contracting a node `v` removes it
+//! from the search graph and, for every pair of its still-uncontracted
+//! neighbors `(u, w)`, adds a shortcut edge `u -> w` with weight
+//! `weight(u, v) + weight(v, w)` whenever a local witness search (bounded
+//! Dijkstra excluding `v` and every already-contracted node) can't find a
+//! path of that length or shorter without going through `v`. This preserves
+//! shortest-path distances between any two uncontracted nodes while letting
+//! a later query only ever move from lower- to higher-contraction-order
+//! nodes, which is what makes the bidirectional query below fast: both
+//! searches explore a narrow "upward" fan instead of the full graph.
+//!
+//! This module assumes the street graph's pedestrian edges are reciprocal
+//! (every `u -> v` edge has a matching `v -> u` edge of the same weight),
+//! which holds for the OSM-derived walking network built elsewhere in this
+//! crate.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use hashbrown::{HashMap, HashSet};
+use petgraph::graph::NodeIndex;
+
+use super::network::StreetGraph;
+
+/// A local witness search only needs to confirm that *some* path avoiding
+/// the contracted node is at least as short as the shortcut candidate; it
+/// doesn't need to explore the whole remaining graph to do that. Capping the
+/// number of settled nodes keeps preprocessing fast — if the cap is hit
+/// before a witness is found, the shortcut is added anyway, which can never
+/// make a query return a wrong (too-long) distance, only occasionally keep
+/// a shortcut that turns out to be redundant.
+const WITNESS_SEARCH_NODE_LIMIT: usize = 50;
+
+/// One edge in a [`ContractionHierarchy`]'s `up` search graph: either an
+/// original street-graph edge (`via: None`) or a shortcut inserted while
+/// contracting `via`.
+#[derive(Clone, Copy)]
+struct ChEdge {
+    target: NodeIndex,
+    weight: u32,
+    via: Option<NodeIndex>,
+}
+
+/// A preprocessed street graph: the "upward" edges (toward
+/// later-to-be-contracted nodes) discovered while contracting each node, in
+/// contraction order. Because the street graph's edges are reciprocal (see
+/// module docs), this single `up` graph is enough for both search
+/// directions: a forward search climbs `up` from the source, and a backward
+/// search climbs the very same edges from the target, since an up-edge
+/// `a -> b` always has a same-weight counterpart `b -> a` it could equally
+/// have been recorded as.
+pub struct ContractionHierarchy {
+    up: HashMap<NodeIndex, Vec<ChEdge>>,
+}
+
+impl ContractionHierarchy {
+    /// Preprocesses `graph` once. Call this after building the street graph
+    /// and keep the result around (it's stored on [`crate::TransitModel`]);
+    /// skip it entirely for a short-lived model that only issues a handful
+    /// of point-to-point queries, since preprocessing itself walks every
+    /// node and edge.
+    #[must_use]
+    pub fn build(graph: &StreetGraph) -> Self {
+        let node_count = graph.graph.node_count();
+        let mut working: HashMap<NodeIndex, Vec<ChEdge>> = HashMap::with_capacity(node_count);
+        for node in graph.graph.node_indices() {
+            let edges = graph
+                .edges(node)
+                .map(|edge| ChEdge {
+                    target: edge.target(),
+                    weight: edge.weight().weight,
+                    via: None,
+                })
+                .collect();
+            working.insert(node, edges);
+        }
+
+        let mut order: Vec<NodeIndex> = graph.graph.node_indices().collect();
+        order.sort_by_key(|node| working.get(node).map_or(0, Vec::len));
+
+        let mut contracted: HashSet<NodeIndex> = HashSet::with_capacity(node_count);
+        let mut up: HashMap<NodeIndex, Vec<ChEdge>> = HashMap::with_capacity(node_count);
+
+        for node in order {
+            // Entries pointing at an already-contracted node are stale: that
+            // node's own adjacency list stopped being read once it was
+            // contracted, but nothing removes the reverse reference here.
+            // Filtering them out keeps every "up" edge pointing at a node
+            // that's still uncontracted (i.e. contracted later), which the
+            // bidirectional query depends on.
+            let neighbors: Vec<ChEdge> = working
+                .get(&node)
+                .into_iter()
+                .flatten()
+                .filter(|edge| !contracted.contains(&edge.target))
+                .copied()
+                .collect();
+            for (i, a) in neighbors.iter().enumerate() {
+                for b in neighbors.iter().skip(i + 1) {
+                    if a.target == b.target {
+                        continue;
+                    }
+                    let shortcut_weight = a.weight + b.weight;
+                    let witness = bounded_witness_distance(
+                        &working,
+                        &contracted,
+                        node,
+                        a.target,
+                        b.target,
+                        shortcut_weight,
+                    );
+                    if witness.is_none_or(|dist| dist > shortcut_weight) {
+                        insert_or_tighten(&mut working, a.target, b.target, shortcut_weight, node);
+                        insert_or_tighten(&mut working, b.target, a.target, shortcut_weight, node);
+                    }
+                }
+            }
+
+            for neighbor in &neighbors {
+                up.entry(node).or_default().push(*neighbor);
+            }
+
+            contracted.insert(node);
+        }
+
+        Self { up }
+    }
+
+    /// Shortest walking time between `source` and `target`, or `None` if
+    /// they aren't connected. Runs two restricted Dijkstra searches — both
+    /// climbing [`Self::up`], one from `source` and one from `target` — and
+    /// takes the best meeting point, instead of expanding the whole street
+    /// graph from scratch.
+    #[must_use]
+    pub fn query_distance(&self, source: NodeIndex, target: NodeIndex) -> Option<u32> {
+        if source == target {
+            return Some(0);
+        }
+        let dist_forward = self.restricted_dijkstra(source);
+        let dist_backward = self.restricted_dijkstra(target);
+        dist_forward
+            .iter()
+            .filter_map(|(node, &df)| dist_backward.get(node).map(|&db| df + db))
+            .min()
+    }
+
+    /// Same as [`Self::query_distance`], but also reconstructs the sequence
+    /// of original street-graph nodes the shortest path passes through,
+    /// unpacking every shortcut back into the two edges it replaced.
+    #[must_use]
+    pub fn query_path(&self, source: NodeIndex, target: NodeIndex) -> Option<Vec<NodeIndex>> {
+        if source == target {
+            return Some(vec![source]);
+        }
+        let (dist_forward, pred_forward) = self.restricted_dijkstra_with_predecessors(source);
+        let (dist_backward, pred_backward) = self.restricted_dijkstra_with_predecessors(target);
+
+        let meeting_node = dist_forward
+            .iter()
+            .filter_map(|(&node, &df)| dist_backward.get(&node).map(|&db| (node, df + db)))
+            .min_by_key(|&(_, total)| total)
+            .map(|(node, _)| node)?;
+
+        let mut forward_half = Vec::new();
+        let mut current = meeting_node;
+        while current != source {
+            let (prev, via) = pred_forward[&current];
+            forward_half.push((prev, current, via));
+            current = prev;
+        }
+        forward_half.reverse();
+
+        let mut backward_half = Vec::new();
+        let mut current = meeting_node;
+        while current != target {
+            let (prev, via) = pred_backward[&current];
+            backward_half.push((current, prev, via));
+            current = prev;
+        }
+
+        let mut path = vec![source];
+        for (from, to, via) in forward_half.into_iter().chain(backward_half) {
+            unpack_edge(self, from, to, via, &mut path);
+        }
+        Some(path)
+    }
+
+    fn restricted_dijkstra(&self, start: NodeIndex) -> HashMap<NodeIndex, u32> {
+        let mut distances: HashMap<NodeIndex, u32> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        distances.insert(start, 0);
+        heap.push(Reverse((0u32, start)));
+
+        while let Some(Reverse((cost, node))) = heap.pop() {
+            if cost > *distances.get(&node).unwrap_or(&u32::MAX) {
+                continue;
+            }
+            for edge in self.up.get(&node).into_iter().flatten() {
+                let next_cost = cost + edge.weight;
+                if next_cost < *distances.get(&edge.target).unwrap_or(&u32::MAX) {
+                    distances.insert(edge.target, next_cost);
+                    heap.push(Reverse((next_cost, edge.target)));
+                }
+            }
+        }
+        distances
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn restricted_dijkstra_with_predecessors(
+        &self,
+        start: NodeIndex,
+    ) -> (
+        HashMap<NodeIndex, u32>,
+        HashMap<NodeIndex, (NodeIndex, Option<NodeIndex>)>,
+    ) {
+        let mut distances: HashMap<NodeIndex, u32> = HashMap::new();
+        let mut predecessors: HashMap<NodeIndex, (NodeIndex, Option<NodeIndex>)> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        distances.insert(start, 0);
+        heap.push(Reverse((0u32, start)));
+
+        while let Some(Reverse((cost, node))) = heap.pop() {
+            if cost > *distances.get(&node).unwrap_or(&u32::MAX) {
+                continue;
+            }
+            for edge in self.up.get(&node).into_iter().flatten() {
+                let next_cost = cost + edge.weight;
+                if next_cost < *distances.get(&edge.target).unwrap_or(&u32::MAX) {
+                    distances.insert(edge.target, next_cost);
+                    predecessors.insert(edge.target, (node, edge.via));
+                    heap.push(Reverse((next_cost, edge.target)));
+                }
+            }
+        }
+        (distances, predecessors)
+    }
+}
+
+/// Appends the original-graph nodes between `from` and `to` to `path`
+/// (excluding `from`, which is already the path's last element), recursively
+/// unpacking `via` if the edge is a shortcut rather than an original edge.
+fn unpack_edge(
+    ch: &ContractionHierarchy,
+    from: NodeIndex,
+    to: NodeIndex,
+    via: Option<NodeIndex>,
+    path: &mut Vec<NodeIndex>,
+) {
+    match via {
+        None => path.push(to),
+        Some(via) => {
+            let first_half = find_edge(ch, from, via);
+            let second_half = find_edge(ch, via, to);
+            unpack_edge(ch, from, via, first_half, path);
+            unpack_edge(ch, via, to, second_half, path);
+        }
+    }
+}
+
+/// Looks up the `via` of the edge between `from` and `to`, however it was
+/// recorded. `up` only stores each edge once, indexed by its lower-ranked
+/// endpoint, so `(from, to)` and `(to, from)` are checked interchangeably —
+/// valid because the underlying street edges are reciprocal (see module
+/// docs).
+fn find_edge(ch: &ContractionHierarchy, from: NodeIndex, to: NodeIndex) -> Option<NodeIndex> {
+    ch.up
+        .get(&from)
+        .into_iter()
+        .flatten()
+        .find(|edge| edge.target == to)
+        .or_else(|| {
+            ch.up
+                .get(&to)
+                .into_iter()
+                .flatten()
+                .find(|edge| edge.target == from)
+        })
+        .and_then(|edge| edge.via)
+}
+
+/// Bounded Dijkstra from `source` toward `target`, excluding `skip` (the
+/// node currently being contracted) and every already-contracted node, and
+/// giving up once `max_cost` is exceeded or [`WITNESS_SEARCH_NODE_LIMIT`]
+/// nodes have been settled. Returns the distance to `target` if found within
+/// those bounds.
+fn bounded_witness_distance(
+    graph: &HashMap<NodeIndex, Vec<ChEdge>>,
+    contracted: &HashSet<NodeIndex>,
+    skip: NodeIndex,
+    source: NodeIndex,
+    target: NodeIndex,
+    max_cost: u32,
+) -> Option<u32> {
+    let mut distances: HashMap<NodeIndex, u32> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    distances.insert(source, 0);
+    heap.push(Reverse((0u32, source)));
+    let mut settled = 0usize;
+
+    while let Some(Reverse((cost, node))) = heap.pop() {
+        if node == target {
+            return Some(cost);
+        }
+        if cost > *distances.get(&node).unwrap_or(&u32::MAX) {
+            continue;
+        }
+        if cost > max_cost
+            || settled >= WITNESS_SEARCH_NODE_LIMIT
+            || node == skip
+            || contracted.contains(&node)
+        {
+            continue;
+        }
+        settled += 1;
+
+        for edge in graph.get(&node).into_iter().flatten() {
+            let next_cost = cost + edge.weight;
+            if next_cost <= max_cost
+                && next_cost < *distances.get(&edge.target).unwrap_or(&u32::MAX)
+            {
+                distances.insert(edge.target, next_cost);
+                heap.push(Reverse((next_cost, edge.target)));
+            }
+        }
+    }
+    distances.get(&target).copied()
+}
+
+/// Adds (or tightens an existing) shortcut `from -> to` in the working
+/// contraction graph.
+fn insert_or_tighten(
+    working: &mut HashMap<NodeIndex, Vec<ChEdge>>,
+    from: NodeIndex,
+    to: NodeIndex,
+    weight: u32,
+    via: NodeIndex,
+) {
+    let edges = working.entry(from).or_default();
+    if let Some(existing) = edges.iter_mut().find(|edge| edge.target == to) {
+        if weight < existing.weight {
+            existing.weight = weight;
+            existing.via = Some(via);
+        }
+    } else {
+        edges.push(ChEdge {
+            target: to,
+            weight,
+            via: Some(via),
+        });
+    }
+}
@@ -1,7 +1,9 @@
 //! Pedestrian and street network model
 
 pub mod components;
+pub mod contraction;
 pub mod network;
 
 pub use components::{StreetEdge, StreetNode};
+pub use contraction::ContractionHierarchy;
 pub use network::{IndexedPoint, StreetGraph};
@@ -1,13 +1,26 @@
 //! Public transit data structure and methods to work with it
 
+use std::path::Path;
+
+use super::shapes::Shape;
 use super::types::{FeedMeta, RaptorStopId, Route, RouteId, Stop, StopTime, Time};
+use crate::Error;
+use crate::loading::FeedTransfer;
 use crate::routing::raptor::RaptorError;
+use crate::routing::raptor::RealtimeUpdate;
+use crate::routing::raptor::common::apply_delay;
 use hashbrown::HashMap;
 use petgraph::graph::NodeIndex;
 
 /// Main public transit data structure
 /// based on original microsoft paper
-#[derive(Debug, Clone)]
+///
+/// `NodeIndex`, `CellIndex` and `geo` types used transitively by this and
+/// sibling cacheable structures all implement `serde` themselves; the
+/// `FeedMeta`/`Route`/`RouteId`/`Stop`/`StopTime`/`RaptorStopId`/`Time`
+/// types from [`super::types`] must derive `Serialize`/`Deserialize` too
+/// for this derive to actually round-trip.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PublicTransitData {
     /// All routes
     pub routes: Vec<Route>,
@@ -25,6 +38,103 @@ pub struct PublicTransitData {
     pub node_to_stop: HashMap<NodeIndex, RaptorStopId>,
     /// Metadata for feeds
     pub feeds_meta: Vec<FeedMeta>,
+    /// Raw `transfers.txt` rows, drained and merged into `transfers` during
+    /// model construction so GTFS-declared transfers override computed ones
+    /// for the same stop pair.
+    pub gtfs_transfers: Vec<FeedTransfer>,
+    /// `gtfs_stop_id -> RaptorStopId` reverse lookup, the inverse of
+    /// `stops[id].stop_id`.
+    pub stop_id_index: HashMap<GtfsStopId, RaptorStopId>,
+    /// `gtfs_route_id -> RouteId` reverse lookup. Several `RouteId`s can
+    /// share one GTFS route (e.g. inbound/outbound directions become
+    /// separate RAPTOR routes), so each entry is a list.
+    pub route_id_index: HashMap<GtfsRouteId, Vec<RouteId>>,
+    /// `gtfs_trip_id -> RouteId` reverse lookup. Every RAPTOR route is
+    /// synthesized from exactly one GTFS trip_id, so this is one-to-one.
+    pub trip_id_index: HashMap<GtfsTripId, RouteId>,
+    /// `shapes.txt` polylines, keyed by `shape_id`. A route whose trip had
+    /// no `shape_id` (or one not present in `shapes.txt`) has no entry here;
+    /// callers fall back to stop-to-stop interpolation in that case.
+    pub shapes: HashMap<String, Shape>,
+    /// Precomputed stop-to-stop footpaths (walking time and polyline),
+    /// keyed by `(from_stop, to_stop)`. Built once at model construction by
+    /// the footpath-preparation step in [`crate::loading`]; RAPTOR's own
+    /// transfer durations and on-demand transfer geometry both read from
+    /// this cache instead of re-running a street search per query. A pair
+    /// missing here simply wasn't within the preparation step's
+    /// `footpath_max_length`/`max_transfer_time` cutoffs.
+    pub footpaths: HashMap<(RaptorStopId, RaptorStopId), Footpath>,
+}
+
+/// Newtype around a GTFS `stop_id`, distinguishing it from other plain
+/// `String`s when looking up a [`RaptorStopId`] in [`PublicTransitData::stop_id_index`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct GtfsStopId(pub String);
+
+/// Newtype around a GTFS `route_id`, used as the key of
+/// [`PublicTransitData::route_id_index`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct GtfsRouteId(pub String);
+
+/// Newtype around a GTFS `trip_id`, used as the key of
+/// [`PublicTransitData::trip_id_index`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct GtfsTripId(pub String);
+
+impl std::borrow::Borrow<str> for GtfsStopId {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::borrow::Borrow<str> for GtfsRouteId {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::borrow::Borrow<str> for GtfsTripId {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A single upcoming departure on one trip, as returned by
+/// [`PublicTransitData::nearby_departures`].
+#[derive(Debug, Clone, Copy)]
+pub struct Departure {
+    pub trip_idx: usize,
+    pub departure_time: Time,
+}
+
+/// Departures sharing a `trip_headsign`/`direction_id`, nested inside a
+/// [`RouteGroup`].
+#[derive(Debug, Clone)]
+pub struct HeadsignGroup {
+    pub headsign: String,
+    pub direction_id: Option<u8>,
+    pub departures: Vec<Departure>,
+}
+
+/// Upcoming departures for one GTFS route, broken down by headsign/direction
+/// the way a stop-board display would present them.
+#[derive(Debug, Clone)]
+pub struct RouteGroup {
+    pub route_id: String,
+    pub route_short_name: String,
+    pub route_long_name: String,
+    pub route_color: Option<String>,
+    pub headsign_groups: Vec<HeadsignGroup>,
+}
+
+/// A precomputed walking path between two stops: its duration and the
+/// street polyline connecting them, with a `NAN` placeholder coordinate at
+/// each end for the caller to snap to the stops' exact locations (the same
+/// convention on-demand transfer geometry already uses).
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Footpath {
+    pub duration: Time,
+    pub polyline: Vec<geo::Coord<f64>>,
 }
 
 impl PublicTransitData {
@@ -138,6 +248,17 @@ impl PublicTransitData {
         }
     }
 
+    /// The precomputed footpath from `from_stop` to `to_stop`, if one was
+    /// built at model construction time (i.e. the pair fell within the
+    /// footpath-preparation step's length/time cutoffs).
+    pub fn get_footpath(
+        &self,
+        from_stop: RaptorStopId,
+        to_stop: RaptorStopId,
+    ) -> Option<&Footpath> {
+        self.footpaths.get(&(from_stop, to_stop))
+    }
+
     /// Returns routes through the specified stop
     pub(crate) fn routes_for_stop(&self, stop_idx: RaptorStopId) -> &[RouteId] {
         let start = self.stops[stop_idx].routes_start;
@@ -165,4 +286,217 @@ impl PublicTransitData {
             None
         }
     }
+
+    /// Resolves a GTFS `stop_id` string to its internal [`RaptorStopId`].
+    pub fn stop_id_to_index(&self, stop_id: &str) -> Option<RaptorStopId> {
+        self.stop_id_index.get(stop_id).copied()
+    }
+
+    /// Resolves a [`RaptorStopId`] back to its original GTFS `stop_id`.
+    pub fn index_to_stop_id(&self, stop_id: RaptorStopId) -> Option<&str> {
+        self.stops.get(stop_id).map(|stop| stop.stop_id.as_str())
+    }
+
+    /// Resolves a GTFS `route_id` string to every [`RouteId`] synthesized
+    /// from it (one per direction/pattern).
+    pub fn route_id_to_indices(&self, route_id: &str) -> &[RouteId] {
+        self.route_id_index.get(route_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Resolves a [`RouteId`] back to its original GTFS `route_id`.
+    pub fn index_to_route_id(&self, route_id: RouteId) -> Option<&str> {
+        self.routes
+            .get(route_id)
+            .map(|route| route.route_id.as_str())
+    }
+
+    /// Resolves a GTFS `trip_id` string to the [`RouteId`] synthesized from it.
+    pub fn trip_id_to_index(&self, trip_id: &str) -> Option<RouteId> {
+        self.trip_id_index.get(trip_id).copied()
+    }
+
+    /// Resolves a [`RouteId`] back to its original GTFS `trip_id`.
+    pub fn index_to_trip_id(&self, route_id: RouteId) -> Option<&str> {
+        self.routes
+            .get(route_id)
+            .map(|route| route.trip_id.as_str())
+    }
+
+    /// The `shapes.txt` polyline for `route_id`'s trip, if it named a
+    /// `shape_id` and that shape was present in the feed.
+    pub fn shape_for_route(&self, route_id: RouteId) -> Option<&Shape> {
+        let shape_id = self.routes.get(route_id)?.shape_id.as_deref()?;
+        self.shapes.get(shape_id)
+    }
+
+    /// `StopTime` slice for specific route and trip, with `realtime` delays
+    /// overlaid on top of the static schedule. Does not check whether the
+    /// trip is cancelled; callers that care should check
+    /// `realtime.is_trip_cancelled` first, the same way disruption-aware
+    /// scans check `ServiceDisruptions::is_trip_suspended` before boarding.
+    pub(crate) fn get_trip_with_realtime(
+        &self,
+        route_id: RouteId,
+        trip_idx: usize,
+        realtime: Option<&RealtimeUpdate>,
+    ) -> Result<Vec<StopTime>, RaptorError> {
+        let trip = self.get_trip(route_id, trip_idx)?;
+        let Some(realtime) = realtime else {
+            return Ok(trip.to_vec());
+        };
+
+        Ok(trip
+            .iter()
+            .enumerate()
+            .map(|(stop_idx, stop_time)| {
+                let (arrival_delay, departure_delay) =
+                    realtime.delay_at(route_id, trip_idx, stop_idx);
+                StopTime {
+                    arrival: apply_delay(stop_time.arrival, arrival_delay),
+                    departure: apply_delay(stop_time.departure, departure_delay),
+                }
+            })
+            .collect())
+    }
+
+    /// Same as [`Self::get_source_departures`], but with `realtime` overlaid:
+    /// cancelled trips are dropped and surviving departures carry their
+    /// reported departure delay before the time-window filter is applied.
+    pub(crate) fn get_source_departures_with_realtime(
+        &self,
+        source: RaptorStopId,
+        min_departure: Time,
+        max_departure: Time,
+        realtime: &RealtimeUpdate,
+    ) -> Result<Vec<Time>, RaptorError> {
+        self.validate_stop(source)?;
+
+        let mut departures = Vec::new();
+        let routes = self.routes_for_stop(source);
+
+        for &route_id in routes {
+            let route_stops = self.get_route_stops(route_id)?;
+
+            if let Some(stop_idx) = route_stops.iter().position(|&stop| stop == source) {
+                let route = &self.routes[route_id];
+
+                for trip_idx in 0..route.num_trips {
+                    if realtime.is_trip_cancelled(route_id, trip_idx) {
+                        continue;
+                    }
+
+                    let trip = self.get_trip(route_id, trip_idx)?;
+                    let (_, departure_delay) = realtime.delay_at(route_id, trip_idx, stop_idx);
+                    let departure_time = apply_delay(trip[stop_idx].departure, departure_delay);
+
+                    if departure_time >= min_departure && departure_time <= max_departure {
+                        departures.push(departure_time);
+                    }
+                }
+            }
+        }
+
+        departures.sort_unstable();
+        departures.dedup();
+
+        Ok(departures)
+    }
+
+    /// Next `limit` departures from `source` at or after `after`, grouped by
+    /// GTFS route and then by headsign/direction, following the nearby-
+    /// departures layout used for stop-board displays.
+    ///
+    /// Routes and headsign groups appear in order of their earliest
+    /// departure in the returned set; within a headsign group, departures
+    /// are sorted by time.
+    pub fn nearby_departures(
+        &self,
+        source: RaptorStopId,
+        after: Time,
+        limit: usize,
+    ) -> Result<Vec<RouteGroup>, Error> {
+        self.validate_stop(source)
+            .map_err(|e| Error::InvalidData(e.to_string()))?;
+
+        let mut candidates: Vec<(RouteId, usize, Time)> = Vec::new();
+        for &route_id in self.routes_for_stop(source) {
+            let route_stops = self
+                .get_route_stops(route_id)
+                .map_err(|e| Error::InvalidData(e.to_string()))?;
+            let Some(stop_idx) = route_stops.iter().position(|&stop| stop == source) else {
+                continue;
+            };
+            let route = &self.routes[route_id];
+
+            for trip_idx in 0..route.num_trips {
+                let trip = self
+                    .get_trip(route_id, trip_idx)
+                    .map_err(|e| Error::InvalidData(e.to_string()))?;
+                let departure_time = trip[stop_idx].departure;
+                if departure_time >= after {
+                    candidates.push((route_id, trip_idx, departure_time));
+                }
+            }
+        }
+
+        candidates.sort_unstable_by_key(|&(_, _, departure_time)| departure_time);
+        candidates.truncate(limit);
+
+        let mut groups: Vec<RouteGroup> = Vec::new();
+        for (route_id, trip_idx, departure_time) in candidates {
+            let route = &self.routes[route_id];
+
+            let group_idx = groups
+                .iter()
+                .position(|group| group.route_id == route.route_id)
+                .unwrap_or_else(|| {
+                    groups.push(RouteGroup {
+                        route_id: route.route_id.clone(),
+                        route_short_name: route.route_short_name.clone(),
+                        route_long_name: route.route_long_name.clone(),
+                        route_color: route.route_color.clone(),
+                        headsign_groups: Vec::new(),
+                    });
+                    groups.len() - 1
+                });
+            let headsign_groups = &mut groups[group_idx].headsign_groups;
+
+            let headsign_idx = headsign_groups
+                .iter()
+                .position(|group| {
+                    group.headsign == route.trip_headsign
+                        && group.direction_id == route.direction_id
+                })
+                .unwrap_or_else(|| {
+                    headsign_groups.push(HeadsignGroup {
+                        headsign: route.trip_headsign.clone(),
+                        direction_id: route.direction_id,
+                        departures: Vec::new(),
+                    });
+                    headsign_groups.len() - 1
+                });
+
+            headsign_groups[headsign_idx].departures.push(Departure {
+                trip_idx,
+                departure_time,
+            });
+        }
+
+        Ok(groups)
+    }
+
+    /// Write this data set to `path` as a compact binary file, so it can be
+    /// reloaded with [`Self::load_from_path`] instead of rebuilt from GTFS.
+    pub fn save_to_path(&self, path: &Path) -> Result<(), Error> {
+        let file = std::fs::File::create(path)?;
+        bincode::serialize_into(std::io::BufWriter::new(file), self)
+            .map_err(|e| Error::InvalidData(format!("Failed to serialize transit data: {e}")))
+    }
+
+    /// Load a data set previously written with [`Self::save_to_path`].
+    pub fn load_from_path(path: &Path) -> Result<Self, Error> {
+        let file = std::fs::File::open(path)?;
+        bincode::deserialize_from(std::io::BufReader::new(file))
+            .map_err(|e| Error::InvalidData(format!("Failed to deserialize transit data: {e}")))
+    }
 }
@@ -1,7 +1,12 @@
 //! Модель данных общественного транспорта
 
 pub mod data;
+pub mod shapes;
 pub mod types;
 
-pub use data::PublicTransitData;
+pub use data::{
+    Departure, Footpath, GtfsRouteId, GtfsStopId, GtfsTripId, HeadsignGroup, PublicTransitData,
+    RouteGroup,
+};
+pub use shapes::Shape;
 pub use types::{RaptorStopId, Route, RouteId, Stop, StopTime, Time};
@@ -0,0 +1,132 @@
+//! `shapes.txt` polylines, used to draw a transit leg along the vehicle's
+//! actual path instead of straight lines through its stops.
+
+use geo::{LineString, Point};
+
+use crate::geo_utils::haversine_distance_meters;
+
+/// One `shapes.txt` polyline (already sorted by `shape_pt_sequence`), with
+/// cumulative great-circle distance in meters from its first point
+/// precomputed so a leg can be sliced out of it without re-walking the
+/// whole shape on every lookup.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Shape {
+    points: Vec<Point<f64>>,
+    cumulative_distance: Vec<f64>,
+}
+
+impl Shape {
+    pub fn new(points: Vec<Point<f64>>) -> Self {
+        let mut cumulative_distance = Vec::with_capacity(points.len());
+        let mut total = 0.0;
+        for (idx, &point) in points.iter().enumerate() {
+            if idx > 0 {
+                total += haversine_distance_meters(points[idx - 1], point);
+            }
+            cumulative_distance.push(total);
+        }
+        Self {
+            points,
+            cumulative_distance,
+        }
+    }
+
+    /// Cumulative distance in meters to the point on the shape closest to
+    /// `stop`, found by projecting `stop` onto each segment in turn.
+    pub fn measure_along(&self, stop: Point<f64>) -> f64 {
+        if self.points.len() < 2 {
+            return 0.0;
+        }
+
+        let mut best_distance = f64::MAX;
+        let mut best_measure = 0.0;
+        for i in 0..self.points.len() - 1 {
+            let (a, b) = (self.points[i], self.points[i + 1]);
+            let segment_length = self.cumulative_distance[i + 1] - self.cumulative_distance[i];
+            let fraction = projected_fraction(a, b, stop);
+            let projected = Point::new(
+                a.x() + fraction * (b.x() - a.x()),
+                a.y() + fraction * (b.y() - a.y()),
+            );
+            let distance = haversine_distance_meters(stop, projected);
+            if distance < best_distance {
+                best_distance = distance;
+                best_measure = self.cumulative_distance[i] + fraction * segment_length;
+            }
+        }
+        best_measure
+    }
+
+    /// The portion of the shape between `from` and `to` measures (meters,
+    /// as returned by [`Self::measure_along`]), always running from `from`
+    /// to `to` regardless of which one is further along the shape.
+    pub fn slice(&self, from: f64, to: f64) -> LineString<f64> {
+        let (lo, hi, reversed) = if from <= to {
+            (from, to, false)
+        } else {
+            (to, from, true)
+        };
+
+        let mut points = vec![self.interpolate(lo)];
+        for (idx, &measure) in self.cumulative_distance.iter().enumerate() {
+            if measure > lo && measure < hi {
+                points.push(self.points[idx]);
+            }
+        }
+        points.push(self.interpolate(hi));
+        points.dedup_by(|a, b| a.x() == b.x() && a.y() == b.y());
+
+        if reversed {
+            points.reverse();
+        }
+
+        points
+            .into_iter()
+            .map(|p| (p.x(), p.y()))
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// The point on the shape at `measure` meters from its start, clamped
+    /// to the shape's own length.
+    fn interpolate(&self, measure: f64) -> Point<f64> {
+        let Some(&total_length) = self.cumulative_distance.last() else {
+            return self.points.first().copied().unwrap_or(Point::new(0.0, 0.0));
+        };
+        let measure = measure.clamp(0.0, total_length);
+
+        for i in 0..self.cumulative_distance.len().saturating_sub(1) {
+            let (start, end) = (self.cumulative_distance[i], self.cumulative_distance[i + 1]);
+            if measure >= start && measure <= end {
+                let fraction = if end > start {
+                    (measure - start) / (end - start)
+                } else {
+                    0.0
+                };
+                let (a, b) = (self.points[i], self.points[i + 1]);
+                return Point::new(
+                    a.x() + fraction * (b.x() - a.x()),
+                    a.y() + fraction * (b.y() - a.y()),
+                );
+            }
+        }
+        self.points.last().copied().unwrap_or(Point::new(0.0, 0.0))
+    }
+}
+
+/// Fraction along segment `a -> b` closest to `point`, clamped to `[0, 1]`,
+/// using a local equirectangular approximation of the segment — accurate
+/// enough over a single shape segment's short span.
+fn projected_fraction(a: Point<f64>, b: Point<f64>, point: Point<f64>) -> f64 {
+    let lat_scale = a.y().to_radians().cos();
+    let (ax, ay) = (a.x() * lat_scale, a.y());
+    let (bx, by) = (b.x() * lat_scale, b.y());
+    let (px, py) = (point.x() * lat_scale, point.y());
+
+    let (dx, dy) = (bx - ax, by - ay);
+    let len_sq = dx * dx + dy * dy;
+    if len_sq == 0.0 {
+        return 0.0;
+    }
+    (((px - ax) * dx + (py - ay) * dy) / len_sq).clamp(0.0, 1.0)
+}
@@ -1,14 +1,21 @@
 pub use crate::MAX_CANDIDATE_STOPS;
 
 // Re-export key components
-pub use crate::algo::isochrone::{IsochroneIndex, calculate_isochrone};
-pub use crate::loading::{TransitModelConfig, create_transit_model};
-pub use crate::model::{PublicTransitData, TransitModel, TransitPoint};
+pub use crate::algo::held_karp::held_karp;
+pub use crate::algo::isochrone::{
+    bulk_accessibility, calculate_banded_isochrone, calculate_banded_isochrone_geojson,
+    calculate_isochrone, calculate_reachability, cumulative_accessibility, IsochroneIndex,
+};
+pub use crate::loading::{create_transit_model, TransitModelConfig};
+pub use crate::model::{
+    Departure, GtfsRouteId, GtfsStopId, GtfsTripId, HeadsignGroup, PublicTransitData, RouteGroup,
+    Shape, TransitModel, TransitPoint,
+};
 pub use crate::routing::multimodal_routing::{
-    MultiModalResult, multimodal_routing, multimodal_routing_one_to_many,
+    multimodal_routing, multimodal_routing_one_to_many, MultiModalResult,
 };
 pub use crate::routing::pareto::{
-    RangeRoutingResult, pareto_range_multimodal_routing, range_multimodal_routing,
+    pareto_range_multimodal_routing, range_multimodal_routing, RangeRoutingResult,
 };
 
 // Core types for the street network
@@ -1,14 +1,17 @@
-use geo::{LineString, Point, line_string};
+use geo::{line_string, LineString, Point};
 use geojson::{Feature, FeatureCollection, Geometry};
 use serde_json::json;
 
 use crate::{
-    Error, MAX_CANDIDATE_STOPS, PublicTransitData, RaptorStopId, Time, TransitModel,
     model::TransitPoint,
     routing::{
         multimodal_routing::TransitCandidate,
-        raptor::{Journey, JourneyLeg, TracedRaptorResult, traced_raptor},
+        raptor::{
+            traced_raptor, Journey, JourneyLeg, RealtimeUpdate, TracedRaptorOptions,
+            TracedRaptorResult,
+        },
     },
+    Error, PublicTransitData, RaptorStopId, Time, TransitModel, MAX_CANDIDATE_STOPS,
 };
 
 /// Represents a walking leg outside the transit network.
@@ -62,6 +65,13 @@ impl WalkingLeg {
                 "to_name": self.to_name,
                 "departure_time": self.departure_time,
                 "arrival_time": self.arrival_time,
+                // Walking legs aren't scheduled, so the predicted time is
+                // always the scheduled time and the delay is always zero;
+                // these are carried anyway so every leg kind in the
+                // FeatureCollection exposes the same realtime properties.
+                "scheduled_departure_time": self.departure_time,
+                "scheduled_arrival_time": self.arrival_time,
+                "delay": 0,
                 "duration": self.duration,
             }
         });
@@ -181,6 +191,7 @@ impl DetailedJourney {
                         departure_time,
                         to_stop,
                         arrival_time,
+                        ..
                     } => Self::transit_leg_feature(
                         transit_data,
                         *route_id,
@@ -248,7 +259,12 @@ impl DetailedJourney {
             .unwrap_or_default();
         let to_name = transit_data.transit_stop_name(to_stop).unwrap_or_default();
 
-        let mut coords = vec![(from_loc.x(), from_loc.y())];
+        let mut straight_line_coords = vec![(from_loc.x(), from_loc.y())];
+        // Falls back to the leg's own (possibly realtime-adjusted) times if
+        // the route/trip can't be looked back up, so a malformed journey
+        // still renders a delay of zero rather than a bogus one.
+        let mut scheduled_departure = departure_time;
+        let mut scheduled_arrival = arrival_time;
         if let Ok(route_stops) = transit_data.get_route_stops(route_id) {
             if let (Some(start_idx), Some(end_idx)) = (
                 route_stops.iter().position(|&s| s == from_stop),
@@ -261,12 +277,27 @@ impl DetailedJourney {
                 };
                 for idx in range {
                     let stop_loc = transit_data.transit_stop_location(route_stops[idx]);
-                    coords.push((stop_loc.x(), stop_loc.y()));
+                    straight_line_coords.push((stop_loc.x(), stop_loc.y()));
+                }
+                if let Ok(scheduled_stops) = transit_data.get_trip(route_id, trip_id) {
+                    scheduled_departure = scheduled_stops[start_idx].departure;
+                    scheduled_arrival = scheduled_stops[end_idx].arrival;
                 }
             }
         }
-        coords.push((to_loc.x(), to_loc.y()));
-        let line: LineString<_> = coords.into();
+        straight_line_coords.push((to_loc.x(), to_loc.y()));
+
+        // Prefer the trip's actual `shapes.txt` polyline, sliced between the
+        // two stops' projected positions, over stringing straight segments
+        // through the intermediate stops; only routes with no usable shape
+        // fall back to the straight-line reconstruction above.
+        let line: LineString<f64> = transit_data
+            .shape_for_route(route_id)
+            .map(|shape| shape.slice(shape.measure_along(from_loc), shape.measure_along(to_loc)))
+            .filter(|line| line.coords().count() >= 2)
+            .unwrap_or_else(|| straight_line_coords.into());
+
+        let delay = i64::from(departure_time) - i64::from(scheduled_departure);
 
         let value = json!({
             "type": "Feature",
@@ -280,6 +311,9 @@ impl DetailedJourney {
                 "to_name": to_name,
                 "departure_time": departure_time,
                 "arrival_time": arrival_time,
+                "scheduled_departure_time": scheduled_departure,
+                "scheduled_arrival_time": scheduled_arrival,
+                "delay": delay,
                 "duration": arrival_time - departure_time,
             }
         });
@@ -347,6 +381,10 @@ impl DetailedJourney {
 }
 
 /// Traced multimodal routing from one point to another.
+///
+/// `realtime`, if given, is the same live GTFS-RT delay/cancellation overlay
+/// `traced_raptor` accepts; the resulting [`DetailedJourney`]'s transit legs
+/// then carry both the scheduled and realtime-adjusted times.
 #[allow(clippy::missing_panics_doc)]
 pub fn traced_multimodal_routing(
     transit_model: &TransitModel,
@@ -354,6 +392,7 @@ pub fn traced_multimodal_routing(
     end: &TransitPoint,
     departure_time: Time,
     max_transfers: usize,
+    realtime: Option<&RealtimeUpdate>,
 ) -> Result<Option<DetailedJourney>, Error> {
     let transit_data = &transit_model.transit_data;
     let direct_walking = start.walking_time_to(end);
@@ -377,6 +416,10 @@ pub fn traced_multimodal_routing(
                 Some(egress_stop),
                 departure_time + access_time,
                 max_transfers,
+                &TracedRaptorOptions {
+                    realtime,
+                    ..Default::default()
+                },
             ) {
                 let transit_time = journey.arrival_time - (departure_time + access_time);
                 let total_time = access_time + transit_time + egress_time;
@@ -438,3 +481,166 @@ pub fn traced_multimodal_routing(
     }
     Ok(None)
 }
+
+/// One candidate kept in the running Pareto frontier built by
+/// [`traced_multimodal_routing_alternatives`]: the three criteria it's
+/// compared on, plus enough to build a [`DetailedJourney`] once it survives.
+struct FrontierCandidate {
+    arrival_time: Time,
+    transfers: usize,
+    walking_time: Time,
+    kind: FrontierKind,
+}
+
+enum FrontierKind {
+    Walking,
+    Transit {
+        journey: Journey,
+        access_stop: RaptorStopId,
+        egress_stop: RaptorStopId,
+        access_time: Time,
+        egress_time: Time,
+    },
+}
+
+impl FrontierCandidate {
+    fn into_journey(
+        self,
+        start: &TransitPoint,
+        end: &TransitPoint,
+        transit_data: &PublicTransitData,
+        departure_time: Time,
+    ) -> DetailedJourney {
+        match self.kind {
+            FrontierKind::Walking => {
+                DetailedJourney::walking_only(start, end, departure_time, self.walking_time)
+            }
+            FrontierKind::Transit {
+                journey,
+                access_stop,
+                egress_stop,
+                access_time,
+                egress_time,
+            } => DetailedJourney::with_transit(
+                start,
+                end,
+                transit_data,
+                access_stop,
+                egress_stop,
+                access_time,
+                egress_time,
+                journey,
+                departure_time,
+            ),
+        }
+    }
+}
+
+/// Whether `a` is at least as good as `b` on every criterion and strictly
+/// better on at least one, i.e. `b` is pointless to keep once `a` is around.
+fn dominates(a: &FrontierCandidate, b: &FrontierCandidate) -> bool {
+    a.arrival_time <= b.arrival_time
+        && a.transfers <= b.transfers
+        && a.walking_time <= b.walking_time
+        && (a.arrival_time < b.arrival_time
+            || a.transfers < b.transfers
+            || a.walking_time < b.walking_time)
+}
+
+/// Inserts `candidate` into `frontier`, dropping it if an existing member
+/// already dominates it, and otherwise evicting every existing member that
+/// `candidate` itself dominates.
+fn insert_into_frontier(frontier: &mut Vec<FrontierCandidate>, candidate: FrontierCandidate) {
+    if frontier
+        .iter()
+        .any(|existing| dominates(existing, &candidate))
+    {
+        return;
+    }
+    frontier.retain(|existing| !dominates(&candidate, existing));
+    frontier.push(candidate);
+}
+
+/// Traced multimodal routing that returns every non-dominated alternative
+/// instead of collapsing to a single minimal-`total_time` journey.
+///
+/// A candidate is kept if no other candidate arrives at least as early, with
+/// at least as few transfers, and with at least as little walking — so a
+/// journey that's a few minutes slower but transfer-free, or that walks much
+/// less, survives alongside the fastest one. Results are sorted by arrival
+/// time; callers can re-sort by `transfers` or `walking_time` to offer
+/// "fewest transfers" or "least walking" options from the same call.
+#[allow(clippy::missing_panics_doc)]
+pub fn traced_multimodal_routing_alternatives(
+    transit_model: &TransitModel,
+    start: &TransitPoint,
+    end: &TransitPoint,
+    departure_time: Time,
+    max_transfers: usize,
+    realtime: Option<&RealtimeUpdate>,
+) -> Result<Vec<DetailedJourney>, Error> {
+    let transit_data = &transit_model.transit_data;
+    let direct_walking = start.walking_time_to(end);
+    let mut frontier: Vec<FrontierCandidate> = Vec::new();
+
+    if let Some(walk_time) = direct_walking {
+        insert_into_frontier(
+            &mut frontier,
+            FrontierCandidate {
+                arrival_time: departure_time + walk_time,
+                transfers: 0,
+                walking_time: walk_time,
+                kind: FrontierKind::Walking,
+            },
+        );
+    }
+
+    for &(access_stop, access_time) in start.nearest_stops.iter().take(MAX_CANDIDATE_STOPS) {
+        for &(egress_stop, egress_time) in end.nearest_stops.iter().take(MAX_CANDIDATE_STOPS) {
+            // A candidate that already walks at least as much as the direct
+            // route, before even boarding transit, can only arrive later and
+            // transfer more than the walking-only candidate above — it's
+            // dominated no matter what the transit leg does, so skip routing it.
+            if let Some(walk_time) = direct_walking {
+                if access_time + egress_time >= walk_time {
+                    continue;
+                }
+            }
+            if let Ok(TracedRaptorResult::SingleTarget(Some(journey))) = traced_raptor(
+                transit_data,
+                access_stop,
+                Some(egress_stop),
+                departure_time + access_time,
+                max_transfers,
+                &TracedRaptorOptions {
+                    realtime,
+                    ..Default::default()
+                },
+            ) {
+                let arrival_time = journey.arrival_time + egress_time;
+                insert_into_frontier(
+                    &mut frontier,
+                    FrontierCandidate {
+                        arrival_time,
+                        transfers: journey.transfers_count,
+                        walking_time: access_time + egress_time,
+                        kind: FrontierKind::Transit {
+                            journey,
+                            access_stop,
+                            egress_stop,
+                            access_time,
+                            egress_time,
+                        },
+                    },
+                );
+            }
+        }
+    }
+
+    let mut journeys: Vec<DetailedJourney> = frontier
+        .into_iter()
+        .map(|candidate| candidate.into_journey(start, end, transit_data, departure_time))
+        .collect();
+    journeys.sort_by_key(|journey| journey.arrival_time);
+    Ok(journeys)
+}
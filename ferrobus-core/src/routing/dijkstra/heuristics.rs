@@ -0,0 +1,85 @@
+//! A* heuristic and heap-ordering support shared by the plain
+//! (`dijkstra_path_weights`) and path-tracing (`dijkstra_paths`) street
+//! searches: both only switch the heuristic on when a single `target` is
+//! given, falling back to `h = 0` (plain Dijkstra) for one-to-many queries.
+
+use std::cmp::Ordering;
+
+use geo::Point;
+use petgraph::{graph::NodeIndex, visit::IntoEdgeReferences};
+
+use crate::geo_utils::haversine_distance_meters;
+use crate::model::StreetGraph;
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub(super) struct State {
+    /// `g + h`: used to order the heap. Equals `cost` when there is no
+    /// target, reducing to plain Dijkstra.
+    pub(super) priority: u32,
+    /// `g`: the accumulated walking time from `start`.
+    pub(super) cost: u32,
+    pub(super) node: NodeIndex,
+}
+
+// Implement Ord for State to use in BinaryHeap
+impl Ord for State {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Min-heap by priority (reversed from standard Rust BinaryHeap)
+        other.priority.cmp(&self.priority)
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Admissible lower bound on the remaining walking time to `target`: the
+/// haversine distance between `node` and `target`, divided by `max_speed_mps`
+/// (the graph's fastest edge, so the bound never overestimates), truncated
+/// to whole seconds.
+pub(super) fn heuristic(
+    graph: &StreetGraph,
+    node: NodeIndex,
+    target: Option<Point<f64>>,
+    max_speed_mps: f64,
+) -> u32 {
+    let (Some(target), Some(node_weight)) = (target, graph.graph.node_weight(node)) else {
+        return 0;
+    };
+    let distance_meters = haversine_distance_meters(node_weight.geometry, target);
+    (distance_meters / max_speed_mps) as u32
+}
+
+/// The fastest speed implied by any edge in `graph`, in meters per second
+/// (edge geometry length divided by its walking-time weight) — i.e. the
+/// smallest per-meter weight in the graph. Dividing the heuristic's
+/// great-circle distance by this bound, rather than an assumed constant,
+/// keeps it admissible regardless of what walking (or biking) speeds the
+/// graph's edges were actually built with. Zero-weight or empty graphs fall
+/// back to a stationary bound so the heuristic stays finite.
+pub(super) fn max_walking_speed_mps(graph: &StreetGraph) -> f64 {
+    let mut max_speed: f64 = 0.0;
+    for edge in graph.graph.edge_references() {
+        let edge_weight = edge.weight();
+        if edge_weight.weight == 0 {
+            continue;
+        }
+        let length_meters = line_length_meters(&edge_weight.geometry);
+        let speed = length_meters / f64::from(edge_weight.weight);
+        if speed > max_speed {
+            max_speed = speed;
+        }
+    }
+    if max_speed > 0.0 { max_speed } else { 1.5 }
+}
+
+/// Great-circle length of a `LineString`, in meters, summed segment by
+/// segment.
+fn line_length_meters(line: &geo::LineString<f64>) -> f64 {
+    line.coords()
+        .zip(line.coords().skip(1))
+        .map(|(a, b)| haversine_distance_meters(Point::from(*a), Point::from(*b)))
+        .sum()
+}
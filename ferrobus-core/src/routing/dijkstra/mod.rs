@@ -1,5 +1,6 @@
+mod heuristics;
 pub mod regular_dijkstra;
 pub mod traced_dijkstra;
 
 pub use regular_dijkstra::dijkstra_path_weights;
-pub(crate) use traced_dijkstra::dijkstra_paths;
+pub(crate) use traced_dijkstra::{WalkingPath, dijkstra_paths};
@@ -1,33 +1,19 @@
-use std::{cmp::Ordering, collections::BinaryHeap};
+use std::collections::BinaryHeap;
 
 use geo::Coord;
 use hashbrown::HashMap;
 use petgraph::{graph::NodeIndex, visit::EdgeRef};
 
+use super::heuristics::{State, heuristic, max_walking_speed_mps};
 use crate::model::StreetGraph;
 
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct State {
-    cost: u32,
-    node: NodeIndex,
-}
-
-// Implement Ord for State to use in BinaryHeap
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        // Min-heap by cost (reversed from standard Rust BinaryHeap)
-        other.cost.cmp(&self.cost)
-    }
-}
-
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-/// Dijkstra's algorithm for finding shortest paths in the walking network
-/// Returns a map of node indices to walking paths
+/// Dijkstra's algorithm for finding shortest paths in the walking network.
+/// Returns a map of node indices to walking paths.
+///
+/// When `target` is given, search switches to A*: the heap is ordered by
+/// `g + h` instead of `g` alone, where `h` is [`heuristic`]'s admissible
+/// great-circle estimate, which cuts node expansions for point-to-point
+/// queries without changing the shortest paths found.
 pub(crate) fn dijkstra_paths(
     graph: &StreetGraph,
     start: NodeIndex,
@@ -40,14 +26,26 @@ pub(crate) fn dijkstra_paths(
     let mut predecessors: HashMap<NodeIndex, NodeIndex> = HashMap::with_capacity(estimated_nodes);
     let mut heap = BinaryHeap::with_capacity(estimated_nodes / 4);
 
+    let target_geometry = target
+        .and_then(|node| graph.graph.node_weight(node))
+        .map(|n| n.geometry);
+    let max_speed_mps = if target_geometry.is_some() {
+        max_walking_speed_mps(graph)
+    } else {
+        // No heuristic will be evaluated in the one-to-many case; skip the
+        // O(edges) scan entirely.
+        1.0
+    };
+
     // Start node has distance 0
     heap.push(State {
+        priority: heuristic(graph, start, target_geometry, max_speed_mps),
         cost: 0,
         node: start,
     });
     distances.insert(start, 0);
 
-    while let Some(State { cost, node }) = heap.pop() {
+    while let Some(State { cost, node, .. }) = heap.pop() {
         // Check if we've reached the target
         if let Some(target_node) = target {
             if node == target_node {
@@ -65,7 +63,7 @@ pub(crate) fn dijkstra_paths(
         // Check max cost constraint
         if let Some(max) = max_cost {
             if f64::from(cost) > max {
-                break;
+                continue;
             }
         }
 
@@ -80,6 +78,8 @@ pub(crate) fn dijkstra_paths(
                 hashbrown::hash_map::Entry::Vacant(entry) => {
                     entry.insert(next_cost);
                     heap.push(State {
+                        priority: next_cost
+                            + heuristic(graph, next, target_geometry, max_speed_mps),
                         cost: next_cost,
                         node: next,
                     });
@@ -89,6 +89,8 @@ pub(crate) fn dijkstra_paths(
                     if next_cost < *entry.get() {
                         *entry.get_mut() = next_cost;
                         heap.push(State {
+                            priority: next_cost
+                                + heuristic(graph, next, target_geometry, max_speed_mps),
                             cost: next_cost,
                             node: next,
                         });
@@ -0,0 +1,382 @@
+//! Frequency-based hyperpath ("optimal strategy") routing: the
+//! Spiess-Florian algorithm, for networks where riders don't plan around a
+//! timetable and simply board the first attractive vehicle to show up.
+//!
+//! This is an alternative to RAPTOR for places where only line frequencies
+//! (not exact schedules) are meaningful. Rather than a single best journey,
+//! it produces a *hyperpath*: at each stop, the small set of lines (and
+//! walk links) worth waiting for, each with a probability of being the one
+//! that arrives first. A route segment between two consecutive stops on a
+//! GTFS trip is treated as a link with frequency `1 / headway` (derived
+//! from how often the route's trips run) and in-vehicle time `c_a`; a walk
+//! link has frequency `f64::INFINITY`, since it's always available with no
+//! wait, so once one becomes attractive it's taken deterministically.
+//!
+//! The algorithm computes, for every stop, the expected remaining travel
+//! time `u` to the target and the accumulated frequency `f` of its
+//! attractive set, following the recursion from Spiess & Florian (1989):
+//! processing a candidate link `a = (i, j)` only becomes valid once `u_j`
+//! is known, so this is implemented as a generalized Dijkstra running
+//! *backward* from `target` — instead of relaxing a single best
+//! predecessor, each accepted link combines into `i`'s running average:
+//!
+//! ```text
+//! u_i = (f_i * u_i + f_a * (u_j + c_a)) / (f_i + f_a)
+//! f_i = f_i + f_a
+//! ```
+//!
+//! included only while `u_i >= u_j + c_a`, i.e. only while the candidate
+//! still improves (or ties) `i`'s current expectation.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::routing::raptor::RaptorError;
+use crate::{PublicTransitData, RaptorStopId, RouteId, Time};
+
+/// One link in a stop's attractive set: a route segment to the next stop,
+/// or a walking transfer (`route_id: None`).
+#[derive(Debug, Clone, Copy)]
+struct CandidateLink {
+    from_stop: RaptorStopId,
+    to_stop: RaptorStopId,
+    route_id: Option<RouteId>,
+    travel_time: Time,
+    /// Vehicles (or, for a walk link, arrivals) per second.
+    frequency: f64,
+}
+
+/// One link in the attractive set (hyperpath) of some stop, with its final
+/// boarding probability once the whole network has been processed.
+#[derive(Debug, Clone, Copy)]
+pub struct HyperpathLink {
+    pub from_stop: RaptorStopId,
+    pub to_stop: RaptorStopId,
+    /// `None` for a walking link.
+    pub route_id: Option<RouteId>,
+    pub travel_time: Time,
+    pub frequency: f64,
+    /// Probability that, having reached `from_stop`, this is the link a
+    /// rider boards: `frequency / combined_frequency_at(from_stop)`.
+    pub boarding_probability: f64,
+}
+
+/// The hyperpath from `source` to `target`: every attractive link reachable
+/// by following the strategy forward from `source`, plus the expected
+/// total travel time under it.
+#[derive(Debug, Clone)]
+pub struct HyperpathResult {
+    pub source: RaptorStopId,
+    pub target: RaptorStopId,
+    /// Expected travel time in seconds, or `None` if `target` isn't
+    /// reachable from `source` through any attractive link.
+    pub expected_travel_time: Option<f64>,
+    pub links: Vec<HyperpathLink>,
+}
+
+/// Orders candidate relaxations by ascending `u_j + c_a`, smallest first,
+/// for use in a min-heap (`BinaryHeap` is a max-heap, so comparisons are
+/// reversed).
+struct HeapEntry {
+    key: f64,
+    link: CandidateLink,
+}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+impl Eq for HeapEntry {}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.key.total_cmp(&self.key)
+    }
+}
+
+/// Combines a newly-accepted link of frequency `f_a` and value `val`
+/// (`u_j + c_a`) into the running `(f_i, u_i)` for its tail stop. A walk
+/// link (`f_a` infinite) fixes `u_i` at `val` deterministically; once `f_i`
+/// is already infinite (a walk link was already accepted), nothing further
+/// can improve on it.
+fn combine(f_i: f64, u_i: f64, f_a: f64, val: f64) -> (f64, f64) {
+    if f_i.is_infinite() {
+        return (f_i, u_i);
+    }
+    if f_a.is_infinite() {
+        return (f64::INFINITY, val);
+    }
+    if f_i == 0.0 {
+        // No link has been accepted for this stop yet, so `u_i` is still
+        // the `f64::INFINITY` placeholder rather than a real average to
+        // blend against: `0.0 * INFINITY` would otherwise produce NaN.
+        return (f_a, val);
+    }
+    let new_f = f_i + f_a;
+    let new_u = f_i.mul_add(u_i, f_a * val) / new_f;
+    (new_f, new_u)
+}
+
+/// The frequency implied by a route's trips, in vehicles per second:
+/// `(num_trips - 1) / service_span`, using the first and last trip's
+/// departure from the route's first stop. Routes with only one trip (no
+/// headway to measure) fall back to a low assumed frequency of one trip
+/// per hour, so they're still includable but rarely preferred over a
+/// frequent alternative.
+fn route_frequency(
+    data: &PublicTransitData,
+    route_id: RouteId,
+    num_trips: usize,
+) -> Result<f64, RaptorError> {
+    if num_trips < 2 {
+        return Ok(1.0 / 3600.0);
+    }
+    let first_departure = data.get_trip(route_id, 0)?[0].departure;
+    let last_departure = data.get_trip(route_id, num_trips - 1)?[0].departure;
+    let span = f64::from(last_departure.saturating_sub(first_departure)).max(1.0);
+    Ok(f64::from(u32::try_from(num_trips - 1).unwrap_or(u32::MAX)) / span)
+}
+
+/// Builds the attractive-link strategy from every stop toward `target`,
+/// then returns the subset of it reachable forward from `source`.
+///
+/// # Errors
+///
+/// Returns an error if `source` or `target` aren't valid stop indices.
+pub fn hyperpath_routing(
+    data: &PublicTransitData,
+    source: RaptorStopId,
+    target: RaptorStopId,
+) -> Result<HyperpathResult, RaptorError> {
+    data.validate_stop(source)?;
+    data.validate_stop(target)?;
+
+    let num_stops = data.stops.len();
+
+    // Links arriving at each stop, indexed by `to_stop`, so that once a
+    // stop's `u` improves we can find every candidate it unlocks upstream.
+    let mut incoming: Vec<Vec<CandidateLink>> = vec![Vec::new(); num_stops];
+
+    for (route_id, route) in data.routes.iter().enumerate() {
+        if route.num_trips == 0 {
+            continue;
+        }
+        let stops = data.get_route_stops(route_id)?;
+        if stops.len() < 2 {
+            continue;
+        }
+        let frequency = route_frequency(data, route_id, route.num_trips)?;
+        let first_trip = data.get_trip(route_id, 0)?;
+        for window_start in 0..stops.len() - 1 {
+            let travel_time = first_trip[window_start + 1]
+                .arrival
+                .saturating_sub(first_trip[window_start].departure);
+            incoming[stops[window_start + 1]].push(CandidateLink {
+                from_stop: stops[window_start],
+                to_stop: stops[window_start + 1],
+                route_id: Some(route_id),
+                travel_time,
+                frequency,
+            });
+        }
+    }
+
+    for stop in 0..num_stops {
+        for &(to_stop, duration) in data.get_stop_transfers(stop)? {
+            incoming[to_stop].push(CandidateLink {
+                from_stop: stop,
+                to_stop,
+                route_id: None,
+                travel_time: duration,
+                frequency: f64::INFINITY,
+            });
+        }
+    }
+
+    let mut u = vec![f64::INFINITY; num_stops];
+    let mut f = vec![0.0_f64; num_stops];
+    let mut attractive: Vec<Vec<HyperpathLink>> = vec![Vec::new(); num_stops];
+
+    u[target] = 0.0;
+
+    let mut heap: BinaryHeap<HeapEntry> = BinaryHeap::new();
+    for &link in &incoming[target] {
+        heap.push(HeapEntry {
+            key: f64::from(link.travel_time),
+            link,
+        });
+    }
+
+    while let Some(HeapEntry { link, .. }) = heap.pop() {
+        if f[link.from_stop].is_infinite() {
+            continue;
+        }
+        let val = u[link.to_stop] + f64::from(link.travel_time);
+        if u[link.from_stop] < val {
+            continue;
+        }
+
+        let (new_f, new_u) = combine(f[link.from_stop], u[link.from_stop], link.frequency, val);
+        f[link.from_stop] = new_f;
+        u[link.from_stop] = new_u;
+        attractive[link.from_stop].push(HyperpathLink {
+            from_stop: link.from_stop,
+            to_stop: link.to_stop,
+            route_id: link.route_id,
+            travel_time: link.travel_time,
+            frequency: link.frequency,
+            boarding_probability: 0.0, // filled in once f[link.from_stop] is final
+        });
+
+        for &upstream in &incoming[link.from_stop] {
+            heap.push(HeapEntry {
+                key: u[link.from_stop] + f64::from(upstream.travel_time),
+                link: upstream,
+            });
+        }
+    }
+
+    // Boarding probabilities depend on each stop's *final* combined
+    // frequency, which isn't known until processing finishes, so they're
+    // computed in a second pass instead of while the heap was still live.
+    for links in &mut attractive {
+        for link in links.iter_mut() {
+            let combined = f[link.from_stop];
+            link.boarding_probability = if combined.is_finite() && combined > 0.0 {
+                link.frequency / combined
+            } else {
+                1.0
+            };
+        }
+    }
+
+    let mut links = Vec::new();
+    let mut visited = vec![false; num_stops];
+    let mut queue = std::collections::VecDeque::from([source]);
+    visited[source] = true;
+    while let Some(stop) = queue.pop_front() {
+        for link in &attractive[stop] {
+            links.push(*link);
+            if !visited[link.to_stop] {
+                visited[link.to_stop] = true;
+                queue.push_back(link.to_stop);
+            }
+        }
+    }
+
+    let expected_travel_time = Some(u[source]).filter(|t| t.is_finite());
+
+    Ok(HyperpathResult {
+        source,
+        target,
+        expected_travel_time,
+        links,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use hashbrown::HashMap;
+
+    use super::hyperpath_routing;
+    use crate::PublicTransitData;
+    use crate::model::{Route, Stop, StopTime};
+
+    /// Two stops joined by a single two-trip route and no walk links, so a
+    /// stop's only incoming candidate toward the target is a finite-frequency
+    /// route segment. Regression test for the `combine` NaN bug: before the
+    /// `f_i == 0.0` special case, the very first relaxation of stop 0 blended
+    /// its `f64::INFINITY` placeholder `u` against a finite value and
+    /// produced `NaN`, which then made `expected_travel_time` report `None`
+    /// even though stop 1 is trivially reachable.
+    fn route_only_network() -> PublicTransitData {
+        PublicTransitData {
+            routes: vec![Route {
+                num_trips: 2,
+                num_stops: 2,
+                stops_start: 0,
+                trips_start: 0,
+                trip_id: "trip-0".to_string(),
+                route_id: "route-0".to_string(),
+                route_short_name: "R0".to_string(),
+                route_long_name: "Route 0".to_string(),
+                route_color: None,
+                trip_headsign: String::new(),
+                direction_id: None,
+                wheelchair_accessible: None,
+                shape_id: None,
+            }],
+            route_stops: vec![0, 1],
+            stop_times: vec![
+                StopTime {
+                    arrival: 0,
+                    departure: 0,
+                },
+                StopTime {
+                    arrival: 100,
+                    departure: 100,
+                },
+                StopTime {
+                    arrival: 1800,
+                    departure: 1800,
+                },
+                StopTime {
+                    arrival: 1900,
+                    departure: 1900,
+                },
+            ],
+            stops: vec![
+                Stop {
+                    stop_id: "stop-0".to_string(),
+                    geometry: geo::Point::new(0.0, 0.0),
+                    routes_start: 0,
+                    routes_len: 1,
+                    transfers_start: 0,
+                    transfers_len: 0,
+                    wheelchair_boarding: None,
+                    min_change_time: None,
+                },
+                Stop {
+                    stop_id: "stop-1".to_string(),
+                    geometry: geo::Point::new(0.0, 0.0),
+                    routes_start: 0,
+                    routes_len: 1,
+                    transfers_start: 0,
+                    transfers_len: 0,
+                    wheelchair_boarding: None,
+                    min_change_time: None,
+                },
+            ],
+            stop_routes: vec![0, 0],
+            transfers: Vec::new(),
+            node_to_stop: HashMap::new(),
+            feeds_meta: Vec::new(),
+            gtfs_transfers: Vec::new(),
+            stop_id_index: HashMap::new(),
+            route_id_index: HashMap::new(),
+            trip_id_index: HashMap::new(),
+            shapes: HashMap::new(),
+            footpaths: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn reachable_via_route_link_has_finite_expected_travel_time() {
+        let data = route_only_network();
+
+        let result = hyperpath_routing(&data, 0, 1).expect("valid stop indices");
+
+        let travel_time = result
+            .expected_travel_time
+            .expect("stop 1 is reachable from stop 0 by a single route segment");
+        assert!(travel_time.is_finite());
+        assert!((travel_time - 100.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,64 @@
+use geojson::{Feature, FeatureCollection, Geometry, Value as GeoJsonValue};
+use serde_json::json;
+
+use crate::routing::hyperpath::HyperpathResult;
+use crate::{Error, PublicTransitData};
+
+impl HyperpathResult {
+    /// Renders the hyperpath as a `GeoJSON` `FeatureCollection`, one feature
+    /// per attractive link, colored by `boarding_probability` (a link a
+    /// rider is virtually certain to take when it shows up, like a walk
+    /// link or the sole attractive route, reads as a solid color; a link
+    /// that's only one of several competing options reads as faint).
+    pub fn to_geojson(&self, transit_data: &PublicTransitData) -> Result<FeatureCollection, Error> {
+        let features = self
+            .links
+            .iter()
+            .map(|link| {
+                let from_loc = transit_data.transit_stop_location(link.from_stop);
+                let to_loc = transit_data.transit_stop_location(link.to_stop);
+                let geometry = Geometry::new(GeoJsonValue::from(&geo::line_string![
+                    (x: from_loc.x(), y: from_loc.y()),
+                    (x: to_loc.x(), y: to_loc.y())
+                ]));
+
+                let value = json!({
+                    "type": "Feature",
+                    "geometry": geometry,
+                    "properties": {
+                        "leg_type": if link.route_id.is_some() { "transit" } else { "walk" },
+                        "route_id": link.route_id,
+                        "from_name": transit_data.transit_stop_name(link.from_stop).unwrap_or_default(),
+                        "to_name": transit_data.transit_stop_name(link.to_stop).unwrap_or_default(),
+                        "travel_time": link.travel_time,
+                        "frequency": link.frequency,
+                        "boarding_probability": link.boarding_probability,
+                        "color": probability_color(link.boarding_probability),
+                    }
+                });
+
+                Feature::from_json_value(value).map_err(|e| Error::GeoJsonError(e.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(FeatureCollection {
+            features,
+            bbox: None,
+            foreign_members: None,
+        })
+    }
+
+    pub fn to_geojson_string(&self, transit_data: &PublicTransitData) -> Result<String, Error> {
+        serde_json::to_string(&self.to_geojson(transit_data)?)
+            .map_err(|e| Error::GeoJsonError(e.to_string()))
+    }
+}
+
+/// A red (rarely boarded) to green (always boarded) hex color for a
+/// boarding probability in `[0, 1]`, for direct use as a map line color.
+fn probability_color(boarding_probability: f64) -> String {
+    let p = boarding_probability.clamp(0.0, 1.0);
+    let red = ((1.0 - p) * 255.0).round() as u8;
+    let green = (p * 255.0).round() as u8;
+    format!("#{red:02x}{green:02x}00")
+}
@@ -0,0 +1,66 @@
+//! Renders a Pareto-optimal set of journeys (as computed by
+//! [`crate::routing::pareto::pareto_range_multimodal_routing`]) to a single
+//! `GeoJSON` `FeatureCollection`, so a client can compare the competing
+//! alternatives on one map instead of one journey at a time.
+
+use geojson::FeatureCollection;
+use serde_json::json;
+
+use super::DetailedJourney;
+use crate::{Error, TransitModel};
+
+/// Converts every journey in `journeys` to a single `FeatureCollection`.
+/// Every feature is tagged with its `journey_index` and the owning
+/// journey's criteria vector (arrival time, transfer count, walking time);
+/// `foreign_members` carries the same criteria tuples keyed by
+/// `pareto_frontier`, so a client can build a legend for the whole
+/// frontier without re-parsing every feature.
+pub fn to_geojson(
+    journeys: &[DetailedJourney],
+    transit_model: &TransitModel,
+) -> Result<FeatureCollection, Error> {
+    let mut features = Vec::new();
+
+    for (journey_index, journey) in journeys.iter().enumerate() {
+        let journey_features = journey.to_geojson(transit_model)?.features;
+        for mut feature in journey_features {
+            let mut properties = feature.properties.take().unwrap_or_default();
+            properties.insert("journey_index".to_string(), json!(journey_index));
+            properties.insert("arrival_time".to_string(), json!(journey.arrival_time));
+            properties.insert("transfers".to_string(), json!(journey.transfers));
+            properties.insert("walking_time".to_string(), json!(journey.walking_time));
+            feature.properties = Some(properties);
+            features.push(feature);
+        }
+    }
+
+    let pareto_frontier: Vec<_> = journeys
+        .iter()
+        .map(|journey| {
+            json!({
+                "arrival_time": journey.arrival_time,
+                "transfers": journey.transfers,
+                "walking_time": journey.walking_time,
+            })
+        })
+        .collect();
+
+    let foreign_members = json!({ "pareto_frontier": pareto_frontier })
+        .as_object()
+        .cloned();
+
+    Ok(FeatureCollection {
+        features,
+        bbox: None,
+        foreign_members,
+    })
+}
+
+/// As [`to_geojson`], serialized to a JSON string.
+pub fn to_geojson_string(
+    journeys: &[DetailedJourney],
+    transit_model: &TransitModel,
+) -> Result<String, Error> {
+    serde_json::to_string(&to_geojson(journeys, transit_model)?)
+        .map_err(|e| Error::GeoJsonError(e.to_string()))
+}
@@ -4,7 +4,9 @@ use serde_json::json;
 
 use crate::{
     Error, PublicTransitData, RaptorStopId, TransitModel,
-    routing::{dijkstra::dijkstra_paths, raptor::JourneyLeg},
+    routing::{
+        dijkstra::dijkstra_paths, dijkstra::traced_dijkstra::WalkingPath, raptor::JourneyLeg,
+    },
     types::{RouteId, Time},
 };
 
@@ -29,6 +31,7 @@ impl DetailedJourney {
                         departure_time,
                         to_stop,
                         arrival_time,
+                        ..
                     } => create_transit_feature(
                         &transit_model.transit_data,
                         idx,
@@ -171,6 +174,18 @@ fn calculate_transfer_geometry(
     to_stop: RaptorStopId,
 ) -> Geometry {
     let transit_data = &transit_model.transit_data;
+
+    // Prefer the footpath precomputed at model build time over a fresh
+    // street search; its endpoints are already snapped to the exact stop
+    // locations, unlike the node path built below.
+    if let Some(footpath) = transit_data.get_footpath(from_stop, to_stop)
+        && footpath.polyline.len() > 1
+    {
+        return Geometry::new(GeoJsonValue::from(&LineString::new(
+            footpath.polyline.clone(),
+        )));
+    }
+
     let source_stop = &transit_data.stops[from_stop];
     let target_stop = &transit_data.stops[to_stop];
     let rtree = transit_model.rtree_ref();
@@ -183,18 +198,11 @@ fn calculate_transfer_geometry(
         .map(|n| n.data);
 
     if let (Some(source_street_node), Some(target_street_node)) = (source_node, target_node) {
-        let mut paths = dijkstra_paths(
-            &transit_model.street_graph,
-            source_street_node,
-            Some(target_street_node),
-            Some(f64::from(transit_model.meta.max_transfer_time)),
-        );
-
-        if let Some(transfer) = paths.remove(&target_street_node)
-            && transfer.nodes().len() > 1
-        {
-            let mut nodes = transfer.into_nodes();
+        let path_nodes = transfer_path_nodes(transit_model, source_street_node, target_street_node);
 
+        if let Some(mut nodes) = path_nodes
+            && nodes.len() > 1
+        {
             // Snap the first and last nodes to the exact stop locations for visual continuity
             let source_loc = transit_data.transit_stop_location(from_stop);
             let target_loc = transit_data.transit_stop_location(to_stop);
@@ -218,6 +226,56 @@ fn calculate_transfer_geometry(
     create_direct_line_geometry(transit_data, from_stop, to_stop)
 }
 
+/// Street-node coordinates for a transfer's walking path, source-to-target
+/// inclusive, with a `NAN` placeholder at each end for the caller to snap to
+/// the exact stop locations. Prefers the street graph's contraction
+/// hierarchy when one was built (near-constant-time regardless of network
+/// size); falls back to A* over the raw graph otherwise. Both branches
+/// reject a path longer than `max_transfer_time`, so a transfer that happens
+/// to miss the precomputed-footpath fast path still renders the same
+/// straight-line fallback a too-long walk gets everywhere else, rather than
+/// the unconstrained global shortest path.
+fn transfer_path_nodes(
+    transit_model: &TransitModel,
+    source_street_node: petgraph::graph::NodeIndex,
+    target_street_node: petgraph::graph::NodeIndex,
+) -> Option<Vec<Coord<f64>>> {
+    let max_transfer_time = f64::from(transit_model.meta.max_transfer_time);
+
+    if let Some(ch) = &transit_model.contraction_hierarchy {
+        let distance = ch.query_distance(source_street_node, target_street_node)?;
+        if f64::from(distance) > max_transfer_time {
+            return None;
+        }
+        let node_path = ch.query_path(source_street_node, target_street_node)?;
+        let mut coords = Vec::with_capacity(node_path.len() + 2);
+        coords.push(Coord {
+            x: f64::NAN,
+            y: f64::NAN,
+        });
+        for node in node_path {
+            if let Some(node_weight) = transit_model.street_graph.graph.node_weight(node) {
+                coords.push(node_weight.geometry.into());
+            }
+        }
+        coords.push(Coord {
+            x: f64::NAN,
+            y: f64::NAN,
+        });
+        return Some(coords);
+    }
+
+    let mut paths = dijkstra_paths(
+        &transit_model.street_graph,
+        source_street_node,
+        Some(target_street_node),
+        Some(max_transfer_time),
+    );
+    paths
+        .remove(&target_street_node)
+        .map(WalkingPath::into_nodes)
+}
+
 fn create_waiting_feature(
     transit_data: &PublicTransitData,
     at_stop: RaptorStopId,
@@ -0,0 +1,42 @@
+//! Wheelchair-accessibility filter for RAPTOR scans: when enabled, trips
+//! whose `wheelchair_accessible` flag isn't the GTFS "1" value are skipped
+//! and transfers only connect stops whose `wheelchair_boarding` flag allows
+//! boarding, the same way [`super::ServiceDisruptions`] gates boarding.
+
+use crate::model::{Route, Stop};
+
+/// Accessibility constraint applied to a RAPTOR scan. Passed in by reference
+/// (`Option<&AccessibilityFilter>`), the same way [`super::ServiceDisruptions`]
+/// and [`super::RealtimeUpdate`] are.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AccessibilityFilter {
+    /// Whether a stop/trip with no GTFS accessibility hint (`0` or absent)
+    /// should be treated as accessible rather than excluded.
+    pub allow_unknown: bool,
+}
+
+impl AccessibilityFilter {
+    pub fn new(allow_unknown: bool) -> Self {
+        Self { allow_unknown }
+    }
+
+    /// Whether `route`'s trips may be boarded under this filter. Every trip
+    /// on a given RAPTOR route shares the same `wheelchair_accessible` flag,
+    /// so this is checked once per route rather than once per trip.
+    pub(crate) fn route_is_accessible(&self, route: &Route) -> bool {
+        match route.wheelchair_accessible {
+            Some(1) => true,
+            Some(_) => false,
+            None => self.allow_unknown,
+        }
+    }
+
+    /// Whether `stop` may be boarded at or alighted from under this filter.
+    pub(crate) fn stop_is_accessible(&self, stop: &Stop) -> bool {
+        match stop.wheelchair_boarding {
+            Some(1) => true,
+            Some(2) => false,
+            Some(_) | None => self.allow_unknown,
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Temporary disruption overlay: lets a query route around a closed stop,
+//! a blocked transfer, or a suspended trip/route for a given time window
+//! without rebuilding the feed.
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::{RaptorStopId, RouteId, Time};
+
+/// A set of temporary closures consulted by the route-scan and foot-path
+/// relaxation steps of `traced_raptor` and `rraptor`. Built per-query (or
+/// once and reused across queries) rather than baked into
+/// `PublicTransitData`, so a caller can simulate "line 5 is down 9-11am,
+/// station A closed all day" without touching the underlying feed.
+#[derive(Debug, Clone, Default)]
+pub struct ServiceDisruptions {
+    closed_stops: HashSet<RaptorStopId>,
+    blocked_transfers: HashSet<(RaptorStopId, RaptorStopId)>,
+    per_trip: HashMap<(RouteId, usize), Vec<(Time, Time)>>,
+    per_route: HashMap<RouteId, Vec<(Time, Time)>>,
+}
+
+impl ServiceDisruptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks `stop` as closed: it can no longer be boarded at or alighted
+    /// from for the whole query.
+    pub fn close_stop(&mut self, stop: RaptorStopId) {
+        self.closed_stops.insert(stop);
+    }
+
+    /// Blocks the foot-path transfer `from -> to`.
+    pub fn block_transfer(&mut self, from: RaptorStopId, to: RaptorStopId) {
+        self.blocked_transfers.insert((from, to));
+    }
+
+    /// Suspends a single trip for `[start, end)`: it cannot be boarded if
+    /// doing so would ride through the window.
+    pub fn suspend_trip(&mut self, route_id: RouteId, trip_id: usize, start: Time, end: Time) {
+        self.per_trip
+            .entry((route_id, trip_id))
+            .or_default()
+            .push((start, end));
+    }
+
+    /// Suspends every trip on `route_id` for `[start, end)`.
+    pub fn suspend_route(&mut self, route_id: RouteId, start: Time, end: Time) {
+        self.per_route
+            .entry(route_id)
+            .or_default()
+            .push((start, end));
+    }
+
+    pub(crate) fn is_stop_closed(&self, stop: RaptorStopId) -> bool {
+        self.closed_stops.contains(&stop)
+    }
+
+    pub(crate) fn is_transfer_blocked(&self, from: RaptorStopId, to: RaptorStopId) -> bool {
+        self.blocked_transfers.contains(&(from, to))
+    }
+
+    /// Returns whether riding `route_id`/`trip_id` over the half-open
+    /// travel window `[departure, arrival)` overlaps a suspended window.
+    pub(crate) fn is_trip_suspended(
+        &self,
+        route_id: RouteId,
+        trip_id: usize,
+        departure: Time,
+        arrival: Time,
+    ) -> bool {
+        self.per_trip
+            .get(&(route_id, trip_id))
+            .into_iter()
+            .flatten()
+            .chain(self.per_route.get(&route_id).into_iter().flatten())
+            .any(|&(start, end)| departure < end && start < arrival)
+    }
+}
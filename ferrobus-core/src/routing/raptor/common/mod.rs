@@ -1,7 +1,15 @@
 // Common RAPTOR components shared between implementations
+mod accessibility;
+mod disruptions;
+mod realtime;
 mod state;
 
+pub use accessibility::AccessibilityFilter;
+pub use disruptions::ServiceDisruptions;
+pub use realtime::RealtimeUpdate;
+pub(crate) use realtime::apply_delay;
 pub use state::{
-    RaptorError, RaptorResult, RaptorState, find_earliest_trip, find_earliest_trip_at_stop,
-    get_target_bound, validate_raptor_inputs,
+    RaptorError, RaptorResult, RaptorState, create_route_queue, find_earliest_trip,
+    find_earliest_trip_at_stop, get_target_bound, min_change_time_for_stop, process_foot_paths,
+    validate_raptor_inputs,
 };
@@ -0,0 +1,73 @@
+//! GTFS-Realtime delay overlay: lets a query read live arrival/departure
+//! delays and trip cancellations on top of the static timetable without
+//! mutating `PublicTransitData` itself.
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::{RouteId, Time};
+
+/// Per-stop delay seconds and trip cancellations from a GTFS-RT `TripUpdates`
+/// feed, keyed by `(route_id, trip_idx, stop_idx)` the same way
+/// `PublicTransitData::get_trip` addresses a scheduled stop time.
+///
+/// Built fresh from each `TripUpdates` poll and passed in by reference
+/// (`Option<&RealtimeUpdate>`), the same way [`super::ServiceDisruptions`]
+/// is: a caller swaps in a new overlay rather than rebuilding the feed.
+#[derive(Debug, Clone, Default)]
+pub struct RealtimeUpdate {
+    /// Delay in seconds (negative for early) applied to a stop's scheduled
+    /// `(arrival, departure)`.
+    delays: HashMap<(RouteId, usize, usize), (i32, i32)>,
+    cancelled_trips: HashSet<(RouteId, usize)>,
+}
+
+impl RealtimeUpdate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the arrival/departure delay, in seconds, observed for
+    /// `route_id`/`trip_idx` at `stop_idx`.
+    pub fn set_delay(
+        &mut self,
+        route_id: RouteId,
+        trip_idx: usize,
+        stop_idx: usize,
+        arrival_delay: i32,
+        departure_delay: i32,
+    ) {
+        self.delays.insert(
+            (route_id, trip_idx, stop_idx),
+            (arrival_delay, departure_delay),
+        );
+    }
+
+    /// Marks `route_id`/`trip_idx` as cancelled for this overlay.
+    pub fn cancel_trip(&mut self, route_id: RouteId, trip_idx: usize) {
+        self.cancelled_trips.insert((route_id, trip_idx));
+    }
+
+    pub(crate) fn is_trip_cancelled(&self, route_id: RouteId, trip_idx: usize) -> bool {
+        self.cancelled_trips.contains(&(route_id, trip_idx))
+    }
+
+    /// Arrival/departure delay, in seconds, recorded for this stop; `(0, 0)`
+    /// if no update was reported for it.
+    pub(crate) fn delay_at(
+        &self,
+        route_id: RouteId,
+        trip_idx: usize,
+        stop_idx: usize,
+    ) -> (i32, i32) {
+        self.delays
+            .get(&(route_id, trip_idx, stop_idx))
+            .copied()
+            .unwrap_or((0, 0))
+    }
+}
+
+/// Applies a delay in seconds to a scheduled time, in `(arrival, departure)`
+/// order, clamping at zero rather than wrapping on a large negative delay.
+pub(crate) fn apply_delay(base: Time, delay: i32) -> Time {
+    (i64::from(base) + i64::from(delay)).max(0) as Time
+}
@@ -1,7 +1,11 @@
+use std::collections::VecDeque;
+
 use fixedbitset::FixedBitSet;
+use hashbrown::HashMap;
 use thiserror::Error;
 
-use crate::{PublicTransitData, RouteId, Time};
+use super::ServiceDisruptions;
+use crate::{PublicTransitData, RaptorStopId, RouteId, Time};
 
 #[derive(Debug)]
 pub struct RaptorState {
@@ -28,6 +32,8 @@ pub enum RaptorError {
     MaxTransfersExceeded,
     #[error("Invalid jorney")]
     InvalidJourney,
+    #[error("Query exceeded its time budget")]
+    TimedOut,
 }
 
 /// Common validation and setup for RAPTOR algorithms
@@ -49,6 +55,20 @@ pub fn validate_raptor_inputs(
     Ok(())
 }
 
+/// Resolves the minimum platform-change buffer enforced at `stop` when
+/// boarding a route other than the one that produced the stop's current
+/// label: the stop's own override if it has one, else the query's
+/// `default_min_change_time`.
+pub fn min_change_time_for_stop(
+    data: &PublicTransitData,
+    stop: RaptorStopId,
+    default_min_change_time: Time,
+) -> Time {
+    data.stops[stop]
+        .min_change_time
+        .unwrap_or(default_min_change_time)
+}
+
 /// Get the target pruning bound for early termination
 pub fn get_target_bound(state: &RaptorState, target: Option<usize>) -> Time {
     if let Some(target_stop) = target {
@@ -151,6 +171,72 @@ pub fn find_earliest_trip_at_stop(
     current_trip_opt.map(|(idx, _)| (idx, current_board_pos))
 }
 
+/// Builds the queue of routes to scan in a round from the set of stops
+/// marked in the previous round: one entry per route touched by a marked
+/// stop, paired with the earliest position along that route at which it
+/// was reached (so the scan never starts later than necessary).
+pub fn create_route_queue(
+    data: &PublicTransitData,
+    marked_stops: &FixedBitSet,
+) -> Result<VecDeque<(RouteId, usize)>, RaptorError> {
+    let mut earliest_pos: HashMap<RouteId, usize> = HashMap::new();
+
+    for stop in marked_stops.ones() {
+        for &route_id in data.routes_for_stop(stop) {
+            let stops = data.get_route_stops(route_id)?;
+            if let Some(pos) = stops.iter().position(|&s| s == stop) {
+                earliest_pos
+                    .entry(route_id)
+                    .and_modify(|existing| *existing = (*existing).min(pos))
+                    .or_insert(pos);
+            }
+        }
+    }
+
+    Ok(earliest_pos.into_iter().collect())
+}
+
+/// Relaxes foot-path transfers from every stop marked in `round`, updating
+/// `state` in place and returning the set of stops newly reached this way.
+///
+/// `disruptions`, if given, removes blocked transfers and closed target
+/// stops from consideration.
+pub fn process_foot_paths(
+    data: &PublicTransitData,
+    target: Option<RaptorStopId>,
+    num_stops: usize,
+    state: &mut RaptorState,
+    round: usize,
+    disruptions: Option<&ServiceDisruptions>,
+) -> Result<FixedBitSet, RaptorError> {
+    let current_marks: Vec<usize> = state.marked_stops[round].ones().collect();
+    let mut new_marks = FixedBitSet::with_capacity(num_stops);
+    let target_bound = get_target_bound(state, target);
+
+    for stop in current_marks {
+        let current_board = state.board_times[round][stop];
+        let transfers = data.get_stop_transfers(stop)?;
+        for &(target_stop, duration) in transfers {
+            if let Some(disruptions) = disruptions {
+                if disruptions.is_stop_closed(target_stop)
+                    || disruptions.is_transfer_blocked(stop, target_stop)
+                {
+                    continue;
+                }
+            }
+            let new_time = current_board.saturating_add(duration);
+            if new_time >= target_bound {
+                continue;
+            }
+            if state.update(round, target_stop, new_time, new_time)? {
+                new_marks.set(target_stop, true);
+            }
+        }
+    }
+
+    Ok(new_marks)
+}
+
 /// Result of the RAPTOR algorithm.
 #[derive(Debug)]
 pub enum RaptorResult {
@@ -0,0 +1,365 @@
+use fixedbitset::FixedBitSet;
+use hashbrown::HashMap;
+use itertools::Itertools;
+
+use super::super::traced::{Journey, JourneyLeg};
+use super::state::{CostEvent, Label, Predecessor, SecondaryCost, dominates, try_insert};
+use crate::model::Transfer;
+use crate::routing::raptor::common::{
+    RaptorError, create_route_queue, find_earliest_trip, validate_raptor_inputs,
+};
+use crate::{PublicTransitData, RaptorStopId, Time};
+
+/// Secondary cost that only charges walking time, reproducing the
+/// arrival-time/total-walk trade-off `mc_raptor` optimized before the
+/// secondary criterion became caller-configurable.
+pub fn walking_time_cost() -> Box<SecondaryCost> {
+    Box::new(|_, event| match event {
+        CostEvent::Transit { .. } => 0,
+        CostEvent::Transfer { duration, .. } => duration,
+    })
+}
+
+/// A trip currently being ridden during a route scan, grouped by `trip_idx`
+/// since every label boarding the same trip shares its arrival times from
+/// that point on and only differs in `secondary_cost`/`transfers` so far.
+struct RidingTrip {
+    boarding_stop: RaptorStopId,
+    boarding_round: usize,
+    boarding_label: usize,
+    departure_time: Time,
+    secondary_cost: Time,
+    transfers: usize,
+}
+
+/// Multi-criteria RAPTOR (McRAPTOR): returns a Pareto-optimal set of
+/// journeys from `source` to `target` trading off arrival time against a
+/// caller-chosen secondary cost (and number of transfers), instead of a
+/// single earliest-arrival journey.
+///
+/// `secondary_cost` is charged against a label for every [`CostEvent`] it is
+/// extended through (boarding a trip, walking a footpath) and accumulates
+/// additively along the label's path; passing a closure that only charges
+/// [`CostEvent::Transfer`] reproduces a total-walking-time criterion, while
+/// one that looks up a fare for [`CostEvent::Transit`] turns the bag into an
+/// arrival-time/fare trade-off instead.
+///
+/// Each stop/round keeps a *bag* of non-dominated `(arrival_time,
+/// secondary_cost, transfers)` labels rather than one label, and every merge
+/// (route scan, footpath relaxation) keeps that bag Pareto-minimal so its
+/// size stays bounded.
+pub fn mc_raptor(
+    data: &PublicTransitData,
+    source: RaptorStopId,
+    target: RaptorStopId,
+    departure_time: Time,
+    max_transfers: usize,
+    secondary_cost: &SecondaryCost,
+) -> Result<Vec<Journey>, RaptorError> {
+    validate_raptor_inputs(data, source, Some(target), departure_time)?;
+
+    let num_stops = data.stops.len();
+    let max_rounds = max_transfers + 1;
+
+    let mut bags: Vec<Vec<Vec<Label>>> = (0..max_rounds)
+        .map(|_| (0..num_stops).map(|_| Vec::new()).collect())
+        .collect();
+    let mut marked_stops: Vec<FixedBitSet> = (0..max_rounds)
+        .map(|_| FixedBitSet::with_capacity(num_stops))
+        .collect();
+
+    bags[0][source].push(Label {
+        arrival_time: departure_time,
+        secondary_cost: 0,
+        transfers: 0,
+        predecessor: Predecessor::Source,
+    });
+    marked_stops[0].set(source, true);
+
+    relax_foot_paths(data, &mut bags, &mut marked_stops, 0, secondary_cost)?;
+
+    for round in 1..max_rounds {
+        let prev_round = round - 1;
+        let mut queue = create_route_queue(data, &marked_stops[prev_round])?;
+        marked_stops[prev_round].clear();
+
+        while let Some((route_id, start_pos)) = queue.pop_front() {
+            let stops = data.get_route_stops(route_id)?;
+            let mut riding: HashMap<usize, Vec<RidingTrip>> = HashMap::new();
+
+            for (trip_stop_idx, &stop) in stops.iter().enumerate().skip(start_pos) {
+                // Board every non-dominated earlier-round label waiting at this stop.
+                for (label_idx, label) in bags[prev_round][stop].iter().enumerate() {
+                    if let Some(trip_idx) =
+                        find_earliest_trip(data, route_id, trip_stop_idx, label.arrival_time)
+                    {
+                        let trip = data.get_trip(route_id, trip_idx)?;
+                        let boarding_cost = secondary_cost(
+                            data,
+                            CostEvent::Transit {
+                                route_id,
+                                trip_id: trip_idx,
+                                from_stop: stop,
+                            },
+                        );
+                        let candidate = RidingTrip {
+                            boarding_stop: stop,
+                            boarding_round: prev_round,
+                            boarding_label: label_idx,
+                            departure_time: trip[trip_stop_idx].departure,
+                            secondary_cost: label.secondary_cost.saturating_add(boarding_cost),
+                            transfers: label.transfers,
+                        };
+                        insert_riding(riding.entry(trip_idx).or_default(), candidate);
+                    }
+                }
+
+                // Propagate every trip currently being ridden to this stop.
+                for (&trip_idx, entries) in &riding {
+                    let trip = data.get_trip(route_id, trip_idx)?;
+                    for entry in entries {
+                        let candidate = Label {
+                            arrival_time: trip[trip_stop_idx].arrival,
+                            secondary_cost: entry.secondary_cost,
+                            transfers: entry.transfers + 1,
+                            predecessor: Predecessor::Transit {
+                                route_id,
+                                trip_id: trip_idx,
+                                from_stop: entry.boarding_stop,
+                                from_round: entry.boarding_round,
+                                from_label: entry.boarding_label,
+                                departure_time: entry.departure_time,
+                            },
+                        };
+                        if try_insert(&mut bags[round][stop], candidate) {
+                            marked_stops[round].set(stop, true);
+                        }
+                    }
+                }
+            }
+        }
+
+        relax_foot_paths(data, &mut bags, &mut marked_stops, round, secondary_cost)?;
+
+        if marked_stops[round].is_clear() {
+            break;
+        }
+    }
+
+    let surviving = pareto_front_at(&bags, target, max_rounds);
+    surviving
+        .into_iter()
+        .map(|(round, idx)| {
+            reconstruct_journey(data, &bags, source, target, round, idx, departure_time)
+        })
+        .collect()
+}
+
+/// Keeps `entries` Pareto-minimal on `(secondary_cost, transfers)`: every
+/// label riding the same trip shares the same future arrival times, so those
+/// two criteria are the only ones left to compare.
+fn insert_riding(entries: &mut Vec<RidingTrip>, candidate: RidingTrip) {
+    let dominated_by_existing = entries.iter().any(|existing| {
+        existing.secondary_cost <= candidate.secondary_cost
+            && existing.transfers <= candidate.transfers
+    });
+    if dominated_by_existing {
+        return;
+    }
+    entries.retain(|existing| {
+        !(candidate.secondary_cost <= existing.secondary_cost
+            && candidate.transfers <= existing.transfers)
+    });
+    entries.push(candidate);
+}
+
+/// Relaxes foot-path transfers from every stop marked in `round`, merging
+/// the resulting labels into the target stops' bags.
+fn relax_foot_paths(
+    data: &PublicTransitData,
+    bags: &mut [Vec<Vec<Label>>],
+    marked_stops: &mut [FixedBitSet],
+    round: usize,
+    secondary_cost: &SecondaryCost,
+) -> Result<(), RaptorError> {
+    let num_stops = bags[round].len();
+    let current_marks: Vec<RaptorStopId> = marked_stops[round].ones().collect();
+    let mut new_marks = FixedBitSet::with_capacity(num_stops);
+
+    for stop in current_marks {
+        let labels = bags[round][stop].clone();
+        let transfers = data.get_stop_transfers(stop)?;
+        for (label_idx, label) in labels.iter().enumerate() {
+            for &Transfer {
+                target_stop,
+                duration,
+                ..
+            } in transfers
+            {
+                let walk_cost = secondary_cost(
+                    data,
+                    CostEvent::Transfer {
+                        from_stop: stop,
+                        to_stop: target_stop,
+                        duration,
+                    },
+                );
+                let candidate = Label {
+                    arrival_time: label.arrival_time.saturating_add(duration),
+                    secondary_cost: label.secondary_cost.saturating_add(walk_cost),
+                    transfers: label.transfers,
+                    predecessor: Predecessor::Transfer {
+                        from_stop: stop,
+                        from_round: round,
+                        from_label: label_idx,
+                        departure_time: label.arrival_time,
+                        duration,
+                    },
+                };
+                if try_insert(&mut bags[round][target_stop], candidate) {
+                    new_marks.set(target_stop, true);
+                }
+            }
+        }
+    }
+
+    marked_stops[round].union_with(&new_marks);
+    Ok(())
+}
+
+/// Collects every label reaching `target` across all rounds and filters it
+/// down to a single cross-round Pareto front, identified by `(round, index)`
+/// pairs into `bags`.
+fn pareto_front_at(
+    bags: &[Vec<Vec<Label>>],
+    target: RaptorStopId,
+    max_rounds: usize,
+) -> Vec<(usize, usize)> {
+    let candidates: Vec<(usize, usize)> = (0..max_rounds)
+        .flat_map(|round| (0..bags[round][target].len()).map(move |idx| (round, idx)))
+        .collect();
+
+    candidates
+        .iter()
+        .copied()
+        .filter(|&(round, idx)| {
+            let label = &bags[round][target][idx];
+            !candidates.iter().any(|&(other_round, other_idx)| {
+                (other_round, other_idx) != (round, idx)
+                    && dominates(&bags[other_round][target][other_idx], label)
+            })
+        })
+        .collect()
+}
+
+fn reconstruct_journey(
+    data: &PublicTransitData,
+    bags: &[Vec<Vec<Label>>],
+    source: RaptorStopId,
+    target: RaptorStopId,
+    round: usize,
+    label_idx: usize,
+    departure_time: Time,
+) -> Result<Journey, RaptorError> {
+    let mut legs = Vec::new();
+    let mut current_stop = target;
+    let mut current_round = round;
+    let mut current_idx = label_idx;
+
+    let arrival_time = bags[round][target][label_idx].arrival_time;
+
+    while current_stop != source {
+        let label = &bags[current_round][current_stop][current_idx];
+        match &label.predecessor {
+            Predecessor::Source => break,
+            Predecessor::Transit {
+                route_id,
+                trip_id,
+                from_stop,
+                from_round,
+                from_label,
+                departure_time,
+            } => {
+                let trip = data.get_trip(*route_id, *trip_id)?;
+                let stops = data.get_route_stops(*route_id)?;
+                let to_idx = stops
+                    .iter()
+                    .position(|&s| s == current_stop)
+                    .ok_or(RaptorError::InvalidJourney)?;
+
+                legs.push(JourneyLeg::Transit {
+                    route_id: *route_id,
+                    trip_id: *trip_id,
+                    from_stop: *from_stop,
+                    departure_time: *departure_time,
+                    to_stop: current_stop,
+                    arrival_time: trip[to_idx].arrival,
+                    bumped: false,
+                    dwell: 0,
+                });
+
+                current_stop = *from_stop;
+                current_round = *from_round;
+                current_idx = *from_label;
+            }
+            Predecessor::Transfer {
+                from_stop,
+                from_round,
+                from_label,
+                departure_time,
+                duration,
+            } => {
+                legs.push(JourneyLeg::Transfer {
+                    from_stop: *from_stop,
+                    departure_time: *departure_time,
+                    to_stop: current_stop,
+                    arrival_time: departure_time.saturating_add(*duration),
+                    duration: *duration,
+                });
+
+                current_stop = *from_stop;
+                current_round = *from_round;
+                current_idx = *from_label;
+            }
+        }
+    }
+
+    legs.reverse();
+
+    let mut walking_legs = Vec::new();
+    for (idx, (prev_leg, next_leg)) in legs.iter().tuple_windows().enumerate() {
+        if let (
+            JourneyLeg::Transit { arrival_time, .. } | JourneyLeg::Transfer { arrival_time, .. },
+            JourneyLeg::Transit {
+                from_stop,
+                departure_time,
+                ..
+            },
+        ) = (prev_leg, next_leg)
+        {
+            walking_legs.push((
+                idx,
+                JourneyLeg::Waiting {
+                    at_stop: *from_stop,
+                    duration: (*departure_time - *arrival_time),
+                },
+            ));
+        }
+    }
+    for (shift, (idx, leg)) in walking_legs.into_iter().enumerate() {
+        legs.insert(idx + shift + 1, leg);
+    }
+
+    let transfers_count = legs
+        .iter()
+        .filter(|leg| matches!(leg, JourneyLeg::Transfer { .. }))
+        .count();
+
+    Ok(Journey {
+        legs,
+        departure_time,
+        arrival_time,
+        transfers_count,
+        provisional: false,
+    })
+}
@@ -0,0 +1,9 @@
+//! Multi-criteria RAPTOR (McRAPTOR): returns a Pareto-optimal set of
+//! journeys trading off arrival time against a caller-chosen secondary cost
+//! and number of transfers, instead of a single earliest-arrival journey.
+
+mod mc_raptor;
+mod state;
+
+pub use mc_raptor::{mc_raptor, walking_time_cost};
+pub use state::{CostEvent, SecondaryCost};
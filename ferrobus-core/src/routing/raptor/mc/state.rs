@@ -0,0 +1,84 @@
+use crate::{PublicTransitData, RaptorStopId, RouteId, Time};
+
+/// Event a label is charged for when it is extended, passed to the
+/// `secondary_cost` closure supplied to [`super::mc_raptor`] so the caller
+/// can define what the bag's second criterion actually measures (walking
+/// time, a per-trip fare, or anything else derivable from the event).
+#[derive(Debug, Clone, Copy)]
+pub enum CostEvent {
+    /// Boarding `trip_id` on `route_id` at `from_stop`.
+    Transit {
+        route_id: RouteId,
+        trip_id: usize,
+        from_stop: RaptorStopId,
+    },
+    /// Walking a footpath of `duration` seconds from `from_stop` to `to_stop`.
+    Transfer {
+        from_stop: RaptorStopId,
+        to_stop: RaptorStopId,
+        duration: Time,
+    },
+}
+
+/// A `secondary_cost` closure charges an extension event a [`Time`]-valued
+/// cost, accumulated additively along a label's full path.
+pub type SecondaryCost = dyn Fn(&PublicTransitData, CostEvent) -> Time + Sync;
+
+/// How a label's stop was reached, pointing back at the exact predecessor
+/// label (by round/stop/index within that stop's bag) rather than just a
+/// stop, since a bag can hold several non-dominated labels per stop.
+#[derive(Debug, Clone)]
+pub(crate) enum Predecessor {
+    Source,
+    Transit {
+        route_id: usize,
+        trip_id: usize,
+        from_stop: RaptorStopId,
+        from_round: usize,
+        from_label: usize,
+        departure_time: Time,
+    },
+    Transfer {
+        from_stop: RaptorStopId,
+        from_round: usize,
+        from_label: usize,
+        departure_time: Time,
+        duration: Time,
+    },
+}
+
+/// A non-dominated criteria tuple `(arrival_time, secondary_cost,
+/// transfers)` plus the backpointer needed to reconstruct the journey it
+/// represents. `secondary_cost` is whatever the caller's `secondary_cost`
+/// closure measures (walking time, fare, ...).
+#[derive(Debug, Clone)]
+pub(crate) struct Label {
+    pub arrival_time: Time,
+    pub secondary_cost: Time,
+    pub transfers: usize,
+    pub predecessor: Predecessor,
+}
+
+/// `a` dominates `b` iff `a` is no worse than `b` in every criterion and
+/// strictly better in at least one.
+pub(crate) fn dominates(a: &Label, b: &Label) -> bool {
+    let no_worse = a.arrival_time <= b.arrival_time
+        && a.secondary_cost <= b.secondary_cost
+        && a.transfers <= b.transfers;
+    let strictly_better = a.arrival_time < b.arrival_time
+        || a.secondary_cost < b.secondary_cost
+        || a.transfers < b.transfers;
+    no_worse && strictly_better
+}
+
+/// Merges `candidate` into a Pareto-minimal bag: drops any existing entry
+/// the candidate dominates, and skips insertion if the candidate is itself
+/// dominated. Returns `true` iff the candidate was inserted.
+pub(crate) fn try_insert(bag: &mut Vec<Label>, candidate: Label) -> bool {
+    if bag.iter().any(|existing| dominates(existing, &candidate)) {
+        return false;
+    }
+    bag.retain(|existing| !dominates(&candidate, existing));
+    bag.push(candidate);
+    true
+}
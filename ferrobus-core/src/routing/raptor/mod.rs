@@ -1,13 +1,20 @@
 // RAPTOR (Round-bAsed Public Transit Optimized Router) implementations
 
 pub mod common;
+pub mod mc;
 pub mod range;
 pub mod regular;
 pub mod traced;
 
 // Re-export main interfaces
 pub(crate) use common::{RaptorError, RaptorResult};
-pub(crate) use range::{RaptorRangeJourney, rraptor};
-pub(crate) use regular::raptor;
+pub(crate) use range::{RaptorRangeJourney, raptor_range_profile, rraptor};
+pub(crate) use regular::{RaptorProfileResult, raptor, raptor_profile};
 
-pub use traced::{Journey, JourneyLeg, TracedRaptorResult, traced_raptor};
+pub use common::{AccessibilityFilter, RealtimeUpdate, ServiceDisruptions};
+pub use mc::{CostEvent, SecondaryCost, mc_raptor, walking_time_cost};
+pub use traced::{
+    Journey, JourneyLeg, JourneyStats, ReservedTimes, TracedRaptorOptions, TracedRaptorResult,
+    TripCapacity, traced_raptor, traced_raptor_pathset, traced_raptor_via,
+    traced_raptor_via_ordered,
+};
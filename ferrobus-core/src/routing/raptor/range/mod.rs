@@ -1,8 +1,48 @@
+//! rRAPTOR: range-query variant of RAPTOR that reuses state across a whole
+//! span of source departure times instead of running from scratch for each
+//! one.
+
+use std::time::{Duration, Instant};
+
 use log::warn;
 
-use super::regular::{create_route_queue, process_foot_paths};
-use super::state::{RaptorError, RaptorState, find_earliest_trip};
-use crate::{PublicTransitData, RaptorStopId, Time};
+mod profile;
+
+pub use profile::raptor_range_profile;
+
+use super::common::{
+    RaptorError, RaptorState, ServiceDisruptions, create_route_queue, find_earliest_trip,
+    min_change_time_for_stop, process_foot_paths,
+};
+use crate::{PublicTransitData, RaptorStopId, RouteId, Time};
+
+/// Starting from `trip_idx`, returns the first trip on `route_id` at
+/// `stop_idx` that isn't suspended over its ride through `stop_idx`, or
+/// `None` if every later departure on the route is also suspended.
+fn find_trip_avoiding_disruption(
+    data: &PublicTransitData,
+    route_id: RouteId,
+    stop_idx: usize,
+    mut trip_idx: usize,
+    disruptions: &ServiceDisruptions,
+) -> Option<usize> {
+    let route = &data.routes[route_id];
+    loop {
+        let stop_time = &data.stop_times[route.trips_start + trip_idx * route.num_stops + stop_idx];
+        if !disruptions.is_trip_suspended(
+            route_id,
+            trip_idx,
+            stop_time.departure,
+            stop_time.arrival,
+        ) {
+            return Some(trip_idx);
+        }
+        trip_idx += 1;
+        if trip_idx >= route.num_trips {
+            return None;
+        }
+    }
+}
 
 #[derive(Debug)]
 /// Result for a range query journey.
@@ -13,6 +53,9 @@ pub struct RaptorRangeJourney {
     pub arrival_time: Option<Time>,
     /// The number of transfers used in the journey.
     pub transfers_used: usize,
+    /// Set when the round loop for this departure was cut short by the
+    /// query's time budget, so `arrival_time` may not be optimal.
+    pub provisional: bool,
 }
 
 /// rRAPTOR: Range Query Version of RAPTOR.
@@ -22,13 +65,30 @@ pub struct RaptorRangeJourney {
 /// within the range, orders them from latest to earliest, and then runs RAPTOR
 /// for each departure time while reusing previously computed labels. The output
 /// is a vector of journeys (one per departure time) for the target stop.
-#[allow(clippy::too_many_lines)]
+///
+/// `timeout`, if given, bounds the overall wall-clock cost: it is checked
+/// between departures and at the top of every round loop. Once it elapses,
+/// the journeys computed so far are returned immediately (the in-progress
+/// one, if any, is marked `provisional`) instead of scanning the remaining
+/// departures.
+///
+/// `disruptions`, if given, routes around temporarily closed stops, blocked
+/// transfers, and suspended trips/routes.
+///
+/// `min_change_time`, if given, is the default platform-change buffer
+/// enforced at a stop before a rider who just alighted a trip can board a
+/// *different* route there (overridable per-stop); continuing on the same
+/// route, or boarding after a footpath transfer, is unaffected.
+#[allow(clippy::too_many_lines, clippy::too_many_arguments)]
 pub fn rraptor(
     data: &PublicTransitData,
     source: RaptorStopId,
     target: Option<RaptorStopId>,
     departure_range: (Time, Time),
     max_transfers: usize,
+    min_change_time: Option<Time>,
+    timeout: Option<Duration>,
+    disruptions: Option<&ServiceDisruptions>,
 ) -> Result<Vec<RaptorRangeJourney>, RaptorError> {
     // Validate source and target.
     data.validate_stop(source)?;
@@ -42,6 +102,8 @@ pub fn rraptor(
     }
     let num_stops = data.stops.len();
     let max_rounds = max_transfers + 1;
+    let deadline = timeout.map(|d| Instant::now() + d);
+    let default_min_change_time = min_change_time.unwrap_or(0);
 
     // Retrieve all departure times from the source within the given range.
     let mut departures =
@@ -53,10 +115,22 @@ pub fn rraptor(
     let mut state = RaptorState::new(num_stops, max_rounds);
     let mut journeys = Vec::with_capacity(departures.len());
 
+    // Parallel to `state`: the route that produced each stop's current-round
+    // board time, or `None` if it was reached by a footpath transfer or is
+    // the search origin. `RaptorState` itself carries no predecessor
+    // information (it's shared with the regular and multi-criteria RAPTOR
+    // variants), so this is tracked locally instead of extending it.
+    let mut board_route: Vec<Vec<Option<RouteId>>> = vec![vec![None; num_stops]; max_rounds];
+
     // For each departure time, update state and run RAPTOR rounds.
     for &dep_time in &departures {
+        if deadline.is_some_and(|d| Instant::now() >= d) {
+            break;
+        }
+
         // Inject the new departure at the source for round 0.
         state.update(0, source, dep_time, dep_time)?;
+        board_route[0][source] = None;
         state.marked_stops[0].set(source, true);
 
         // Process foot-path transfers from the source.
@@ -66,21 +140,37 @@ pub fn rraptor(
                 warn!("Invalid transfer target {target_stop}");
                 continue;
             }
+            if let Some(disruptions) = disruptions {
+                if disruptions.is_stop_closed(target_stop)
+                    || disruptions.is_transfer_blocked(source, target_stop)
+                {
+                    continue;
+                }
+            }
             let new_time = dep_time.saturating_add(duration);
             // For foot-paths we assume no waiting time (arrival equals boarding).
             if state.update(0, target_stop, new_time, new_time)? {
+                board_route[0][target_stop] = None;
                 state.marked_stops[0].set(target_stop, true);
             }
         }
 
+        let mut timed_out = false;
+
         // For rounds 1..max_rounds, first carry over improvements from the previous round.
         for round in 1..max_rounds {
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                timed_out = true;
+                break;
+            }
+
             let prev_round = round - 1;
             // Carry-over step: if the previous round has a better boarding time, propagate it.
             for stop in 0..num_stops {
                 if state.board_times[prev_round][stop] < state.board_times[round][stop] {
                     state.arrival_times[round][stop] = state.arrival_times[prev_round][stop];
                     state.board_times[round][stop] = state.board_times[prev_round][stop];
+                    board_route[round][stop] = board_route[prev_round][stop];
                     state.marked_stops[round].set(stop, true);
                 }
             }
@@ -107,12 +197,41 @@ pub fn rraptor(
 
                 // Find the earliest trip on this route that is catchable.
                 for (idx, &stop) in stops.iter().enumerate().skip(start_pos) {
-                    let earliest_board = state.board_times[prev_round][stop];
-                    if earliest_board == Time::MAX {
+                    if disruptions.is_some_and(|d| d.is_stop_closed(stop)) {
+                        continue;
+                    }
+                    let board_time = state.board_times[prev_round][stop];
+                    if board_time == Time::MAX {
                         continue;
                     }
-                    if let Some(trip_idx) = find_earliest_trip(data, route_id, idx, earliest_board)
+                    // A rider who alighted a *different* route at this stop
+                    // must clear its platform-change buffer before the
+                    // search considers them boarded; continuing on the same
+                    // route, or arriving by footpath, is unaffected.
+                    let earliest_board = match board_route[prev_round][stop] {
+                        Some(prev_route) if prev_route != route_id => {
+                            let buffer =
+                                min_change_time_for_stop(data, stop, default_min_change_time);
+                            board_time
+                                .max(state.arrival_times[prev_round][stop].saturating_add(buffer))
+                        }
+                        _ => board_time,
+                    };
+                    if let Some(mut trip_idx) =
+                        find_earliest_trip(data, route_id, idx, earliest_board)
                     {
+                        if let Some(disruptions) = disruptions {
+                            match find_trip_avoiding_disruption(
+                                data,
+                                route_id,
+                                idx,
+                                trip_idx,
+                                disruptions,
+                            ) {
+                                Some(feasible_idx) => trip_idx = feasible_idx,
+                                None => continue,
+                            }
+                        }
                         current_trip_opt = Some(trip_idx);
                         current_board_pos = idx;
                         break;
@@ -123,12 +242,27 @@ pub fn rraptor(
                     let mut trip = data.get_trip(route_id, trip_idx)?;
 
                     for (trip_stop_idx, &stop) in stops.iter().enumerate().skip(current_board_pos) {
+                        if disruptions.is_some_and(|d| d.is_stop_closed(stop)) {
+                            continue;
+                        }
                         // Check if we can "upgrade" the trip at this stop.
                         let prev_board = state.board_times[prev_round][stop];
                         if prev_board < trip[trip_stop_idx].departure {
-                            if let Some(new_trip_idx) =
+                            if let Some(mut new_trip_idx) =
                                 find_earliest_trip(data, route_id, trip_stop_idx, prev_board)
                             {
+                                if let Some(disruptions) = disruptions {
+                                    match find_trip_avoiding_disruption(
+                                        data,
+                                        route_id,
+                                        trip_stop_idx,
+                                        new_trip_idx,
+                                        disruptions,
+                                    ) {
+                                        Some(feasible_idx) => new_trip_idx = feasible_idx,
+                                        None => new_trip_idx = trip_idx,
+                                    }
+                                }
                                 if new_trip_idx != trip_idx {
                                     trip_idx = new_trip_idx;
                                     trip = data.get_trip(route_id, new_trip_idx)?;
@@ -152,6 +286,7 @@ pub fn rraptor(
 
                         // Only update if this effective boarding time is an improvement.
                         if state.update(round, stop, actual_arrival, effective_board)? {
+                            board_route[round][stop] = Some(route_id);
                             state.marked_stops[round].set(stop, true);
                         }
                         // Prune if we've already exceeded the target bound.
@@ -162,7 +297,13 @@ pub fn rraptor(
                 }
             }
 
-            let new_marks = process_foot_paths(data, target, num_stops, &mut state, round)?;
+            let new_marks =
+                process_foot_paths(data, target, num_stops, &mut state, round, disruptions)?;
+            // Stops reached by a footpath this round are not a transit
+            // board, regardless of what route last labeled them.
+            for stop in new_marks.ones() {
+                board_route[round][stop] = None;
+            }
             state.marked_stops[round].union_with(&new_marks);
 
             // Check if we should continue with this round
@@ -204,8 +345,13 @@ pub fn rraptor(
                 Some(best_arr)
             },
             transfers_used: best_round,
+            provisional: timed_out,
         };
         journeys.push(journey);
+
+        if timed_out {
+            break;
+        }
     }
 
     Ok(journeys)
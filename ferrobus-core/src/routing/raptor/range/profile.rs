@@ -0,0 +1,43 @@
+//! Post-filtering an rRAPTOR range result down to a Pareto-optimal
+//! departure/arrival profile.
+
+use super::RaptorRangeJourney;
+use crate::Time;
+
+/// Filters `journeys` (as produced by [`super::rraptor`]) down to the
+/// Pareto-optimal departure/arrival profile: a journey `a` dominates `b`
+/// when `a.departure_time >= b.departure_time && a.arrival_time <=
+/// b.arrival_time` with at least one strict, i.e. leaving no earlier and
+/// arriving no later makes `b` redundant. Journeys with no arrival at all
+/// are dropped, since they carry no information for a timetable display.
+///
+/// The result is sorted by ascending departure time and is the compact
+/// "if you leave at X you arrive at Y" schedule a profile query is meant
+/// to produce.
+pub fn raptor_range_profile(journeys: Vec<RaptorRangeJourney>) -> Vec<RaptorRangeJourney> {
+    let mut reachable: Vec<RaptorRangeJourney> = journeys
+        .into_iter()
+        .filter(|j| j.arrival_time.is_some())
+        .collect();
+
+    // Scan from the latest departure to the earliest, keeping a journey only
+    // when it beats every later departure's arrival. A journey surviving
+    // that scan is never dominated, since nothing departing later arrives as
+    // early or earlier.
+    reachable.sort_by_key(|j| std::cmp::Reverse(j.departure_time));
+
+    let mut best_arrival = Time::MAX;
+    let mut profile: Vec<RaptorRangeJourney> = Vec::new();
+    for journey in reachable {
+        if journey
+            .arrival_time
+            .is_some_and(|arrival| arrival < best_arrival)
+        {
+            best_arrival = journey.arrival_time.unwrap_or(Time::MAX);
+            profile.push(journey);
+        }
+    }
+
+    profile.reverse();
+    profile
+}
@@ -105,7 +105,7 @@ pub fn raptor(
             }
         }
 
-        let new_marks = process_foot_paths(data, target, num_stops, &mut state, round)?;
+        let new_marks = process_foot_paths(data, target, num_stops, &mut state, round, None)?;
         state.marked_stops[round].union_with(&new_marks);
 
         // If a target is given, check if we can prune the search.
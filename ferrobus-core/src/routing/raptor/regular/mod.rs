@@ -0,0 +1,8 @@
+//! Plain earliest-arrival RAPTOR: reports arrival times only, with no
+//! itinerary reconstruction.
+
+mod default_raptor;
+mod profile;
+
+pub use default_raptor::raptor;
+pub use profile::{RaptorProfileResult, raptor_profile};
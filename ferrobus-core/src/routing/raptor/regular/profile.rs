@@ -0,0 +1,197 @@
+//! Self-pruning range-profile query: runs plain RAPTOR once per departure in
+//! a window, latest to earliest, reusing labels across runs so each later
+//! departure prunes the runs that follow it.
+
+use crate::model::Transfer;
+use crate::routing::raptor::common::{
+    RaptorError, RaptorState, create_route_queue, find_earliest_trip, find_earliest_trip_at_stop,
+    get_target_bound, process_foot_paths, validate_raptor_inputs,
+};
+use crate::{PublicTransitData, RaptorStopId, Time};
+
+/// Result of [`raptor_profile`]: for each departure processed, its
+/// `(departure_time, arrival_time)` label — the Pareto-optimal set is
+/// obtained for free since a label is only recorded when it strictly
+/// improves on every later departure already processed.
+#[derive(Debug)]
+pub enum RaptorProfileResult {
+    /// Labels for the single requested target, earliest departure first.
+    SingleTarget(Vec<(Time, Time)>),
+    /// Labels for every stop, earliest departure first, indexed by stop id.
+    AllTargets(Vec<Vec<(Time, Time)>>),
+}
+
+/// Range-profile version of [`super::raptor`].
+///
+/// Instead of a single `departure_time`, a `[window_start, window_end]` is
+/// given. All distinct trip departure times at `source` within the window
+/// (plus the departures reachable by a single footpath from `source`,
+/// shifted back by the footpath's duration) are collected and processed from
+/// latest to earliest, running the same round-based search as [`super::raptor`]
+/// but WITHOUT resetting `state.best_arrival`/`state.arrival_times` between
+/// departures: a later departure's labels act as an upper bound that prunes
+/// the earlier-departure run, since a journey leaving earlier can never
+/// arrive earlier than one already found leaving later from the same stop.
+///
+/// For each stop a label is appended to its profile only when the current
+/// run strictly improves that stop's arrival time, which produces the
+/// Pareto-optimal `(departure_time, arrival_time)` profile directly, with no
+/// separate filtering pass needed.
+#[allow(clippy::too_many_lines)]
+pub fn raptor_profile(
+    data: &PublicTransitData,
+    source: RaptorStopId,
+    target: Option<RaptorStopId>,
+    window: (Time, Time),
+    max_transfers: usize,
+) -> Result<RaptorProfileResult, RaptorError> {
+    validate_raptor_inputs(data, source, target, window.0)?;
+
+    let num_stops = data.stops.len();
+    let max_rounds = max_transfers + 1;
+    let mut state = RaptorState::new(num_stops, max_rounds);
+
+    let mut departures = collect_window_departures(data, source, window)?;
+    // Process departures from latest to earliest, so each run's labels prune
+    // the next (earlier) run via the shared, never-reset `state`.
+    departures.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut last_recorded = vec![Time::MAX; num_stops];
+    let mut labels: Vec<Vec<(Time, Time)>> = vec![Vec::new(); num_stops];
+
+    for dep_time in departures {
+        // Inject the new departure at the source for round 0.
+        state.update(0, source, dep_time, dep_time)?;
+        state.marked_stops[0].set(source, true);
+
+        // Process foot-path transfers from the source.
+        let transfers = data.get_stop_transfers(source)?;
+        for &Transfer {
+            target_stop,
+            duration,
+            ..
+        } in transfers
+        {
+            let new_time = dep_time.saturating_add(duration);
+            if state.update(0, target_stop, new_time, new_time)? {
+                state.marked_stops[0].set(target_stop, true);
+            }
+        }
+
+        for round in 1..max_rounds {
+            let prev_round = round - 1;
+
+            let mut queue = create_route_queue(data, &state.marked_stops[prev_round])?;
+            state.marked_stops[prev_round].clear();
+
+            let target_bound = get_target_bound(&state, target);
+
+            while let Some((route_id, start_pos)) = queue.pop_front() {
+                let stops = data.get_route_stops(route_id)?;
+
+                if let Some((mut trip_idx, current_board_pos)) = find_earliest_trip_at_stop(
+                    data,
+                    route_id,
+                    stops,
+                    &state.board_times[prev_round],
+                    start_pos,
+                ) {
+                    let mut trip = data.get_trip(route_id, trip_idx)?;
+
+                    for (trip_stop_idx, &stop) in stops.iter().enumerate().skip(current_board_pos)
+                    {
+                        let prev_board = state.board_times[prev_round][stop];
+                        if prev_board < trip[trip_stop_idx].departure {
+                            if let Some(new_trip_idx) =
+                                find_earliest_trip(data, route_id, trip_stop_idx, prev_board)
+                            {
+                                if new_trip_idx != trip_idx {
+                                    trip_idx = new_trip_idx;
+                                    trip = data.get_trip(route_id, new_trip_idx)?;
+                                }
+                            }
+                        }
+
+                        let actual_arrival = trip[trip_stop_idx].arrival;
+                        let effective_board = if let Some(target_stop) = target {
+                            if stop == target_stop {
+                                actual_arrival
+                            } else {
+                                trip[trip_stop_idx].departure
+                            }
+                        } else {
+                            trip[trip_stop_idx].departure
+                        };
+
+                        if state.update(round, stop, actual_arrival, effective_board)? {
+                            state.marked_stops[round].set(stop, true);
+                        }
+                        if effective_board >= target_bound {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            let new_marks = process_foot_paths(data, target, num_stops, &mut state, round, None)?;
+            state.marked_stops[round].union_with(&new_marks);
+
+            if state.marked_stops[round].is_clear() {
+                break;
+            }
+        }
+
+        // Record a label for every stop whose best arrival strictly improved
+        // under this departure.
+        for stop in 0..num_stops {
+            let arrival = state.best_arrival[stop];
+            if arrival != Time::MAX && arrival < last_recorded[stop] {
+                last_recorded[stop] = arrival;
+                labels[stop].push((dep_time, arrival));
+            }
+        }
+    }
+
+    for stop_labels in &mut labels {
+        stop_labels.reverse();
+    }
+
+    if let Some(target_stop) = target {
+        Ok(RaptorProfileResult::SingleTarget(std::mem::take(
+            &mut labels[target_stop],
+        )))
+    } else {
+        Ok(RaptorProfileResult::AllTargets(labels))
+    }
+}
+
+/// Collects the distinct trip departure times at `source` within `window`,
+/// plus the departures at each of `source`'s footpath neighbors, shifted
+/// back by the footpath's duration so they represent the instant one would
+/// need to leave `source` to catch them.
+fn collect_window_departures(
+    data: &PublicTransitData,
+    source: RaptorStopId,
+    window: (Time, Time),
+) -> Result<Vec<Time>, RaptorError> {
+    let mut departures = data.get_source_departures(source, window.0, window.1)?;
+
+    for &Transfer {
+        target_stop,
+        duration,
+        ..
+    } in data.get_stop_transfers(source)?
+    {
+        let neighbor_departures = data.get_source_departures(target_stop, window.0, window.1)?;
+        departures.extend(
+            neighbor_departures
+                .into_iter()
+                .filter_map(|dep| dep.checked_sub(duration))
+                .filter(|&dep| dep >= window.0),
+        );
+    }
+
+    departures.sort_unstable();
+    departures.dedup();
+    Ok(departures)
+}
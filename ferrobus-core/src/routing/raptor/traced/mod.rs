@@ -0,0 +1,17 @@
+//! Earliest-arrival RAPTOR variant that keeps backpointers so a full
+//! itinerary (not just arrival times) can be reconstructed.
+
+mod pathset;
+mod reserved_times;
+mod state;
+mod stats;
+mod traced_raptor;
+mod via;
+
+pub use pathset::traced_raptor_pathset;
+pub use reserved_times::ReservedTimes;
+pub use stats::JourneyStats;
+pub use traced_raptor::{
+    Journey, JourneyLeg, TracedRaptorOptions, TracedRaptorResult, TripCapacity, traced_raptor,
+};
+pub use via::{traced_raptor_via, traced_raptor_via_ordered};
@@ -0,0 +1,142 @@
+//! Stochastic path-set generation: instead of a single earliest-arrival
+//! journey, return a small choice set of structurally distinct itineraries
+//! together with a logit selection probability, following the Fast-Trips
+//! path-set idea.
+
+use hashbrown::{HashMap, HashSet};
+
+use super::{Journey, JourneyLeg, TracedRaptorOptions, TracedRaptorResult, traced_raptor};
+use crate::routing::raptor::common::RaptorError;
+use crate::{PublicTransitData, RaptorStopId, RouteId, Time};
+
+/// Generalized-cost surcharge added (for search-steering purposes only) to
+/// every route a previously-returned journey used, so the next pass is
+/// pushed toward a structurally different path.
+const ROUTE_SURCHARGE: Time = 300;
+
+/// Utility-cost equivalent, in seconds, of one additional transfer.
+const TRANSFER_PENALTY: f64 = 300.0;
+
+/// Dispersion parameter of the logit choice model over generalized cost.
+const LOGIT_SCALE: f64 = 1.0 / 300.0;
+
+/// Returns up to `k` distinct, reasonable journeys from `source` to `target`
+/// rather than only the single earliest-arrival one, each paired with a
+/// selection probability from a logit model over generalized cost (in-vehicle
+/// time + transfer penalty + walk time).
+///
+/// Additional passes penalize the routes used by previously-returned
+/// journeys in the RAPTOR label comparison, steering the search toward
+/// structurally different paths; candidates are deduplicated by their
+/// ordered sequence of `route_id`s.
+pub fn traced_raptor_pathset(
+    data: &PublicTransitData,
+    source: RaptorStopId,
+    target: RaptorStopId,
+    departure_time: Time,
+    max_transfers: usize,
+    k: usize,
+) -> Result<Vec<(Journey, f64)>, RaptorError> {
+    let mut journeys: Vec<Journey> = Vec::new();
+    let mut seen_route_sequences: HashSet<Vec<RouteId>> = HashSet::new();
+    let mut penalties: HashMap<RouteId, Time> = HashMap::new();
+
+    // Bound the number of passes generously beyond `k`: once every route has
+    // been penalized, repeated passes stop turning up anything new.
+    for _ in 0..k.saturating_mul(4).max(1) {
+        if journeys.len() >= k {
+            break;
+        }
+
+        let route_penalty = if penalties.is_empty() {
+            None
+        } else {
+            Some(&penalties)
+        };
+        let result = traced_raptor(
+            data,
+            source,
+            Some(target),
+            departure_time,
+            max_transfers,
+            &TracedRaptorOptions {
+                route_penalty,
+                ..Default::default()
+            },
+        )?;
+        let TracedRaptorResult::SingleTarget(Some(journey)) = result else {
+            break;
+        };
+
+        let route_sequence = route_sequence(&journey);
+        let is_new = seen_route_sequences.insert(route_sequence.clone());
+        if is_new {
+            journeys.push(journey);
+        }
+
+        if route_sequence.is_empty() {
+            // A walking-only journey has nothing left to penalize.
+            break;
+        }
+        for route_id in route_sequence {
+            *penalties.entry(route_id).or_insert(0) += ROUTE_SURCHARGE;
+        }
+    }
+
+    if journeys.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let costs: Vec<f64> = journeys.iter().map(generalized_cost).collect();
+    let min_cost = costs.iter().copied().fold(f64::INFINITY, f64::min);
+    let utilities: Vec<f64> = costs
+        .iter()
+        .map(|cost| (-(cost - min_cost) * LOGIT_SCALE).exp())
+        .collect();
+    let total_utility: f64 = utilities.iter().sum();
+
+    let mut scored: Vec<(Journey, f64)> = journeys
+        .into_iter()
+        .zip(utilities)
+        .map(|(journey, utility)| (journey, utility / total_utility))
+        .collect();
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).expect("probabilities are never NaN"));
+
+    Ok(scored)
+}
+
+/// The ordered sequence of `route_id`s a journey rides, used as the
+/// deduplication key for structurally-equivalent paths.
+fn route_sequence(journey: &Journey) -> Vec<RouteId> {
+    journey
+        .legs
+        .iter()
+        .filter_map(|leg| match leg {
+            JourneyLeg::Transit { route_id, .. } => Some(*route_id),
+            JourneyLeg::Transfer { .. } | JourneyLeg::Waiting { .. } => None,
+        })
+        .collect()
+}
+
+/// In-vehicle time + transfer penalty + walking time, used to rank journeys
+/// in the logit choice model.
+fn generalized_cost(journey: &Journey) -> f64 {
+    let mut in_vehicle_time: Time = 0;
+    let mut walk_time: Time = 0;
+
+    for leg in &journey.legs {
+        match leg {
+            JourneyLeg::Transit {
+                departure_time,
+                arrival_time,
+                ..
+            } => in_vehicle_time += arrival_time.saturating_sub(*departure_time),
+            JourneyLeg::Transfer { duration, .. } => walk_time += duration,
+            JourneyLeg::Waiting { .. } => {}
+        }
+    }
+
+    f64::from(in_vehicle_time)
+        + f64::from(walk_time)
+        + (journey.transfers_count as f64) * TRANSFER_PENALTY
+}
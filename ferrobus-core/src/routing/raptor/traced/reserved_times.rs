@@ -0,0 +1,59 @@
+//! Service-interruption windows (driver breaks, single-track occupancy,
+//! planned disruptions) during which a trip is unavailable, consulted by
+//! `traced_raptor` before it commits a boarding.
+
+use hashbrown::HashMap;
+
+use crate::{RouteId, Time};
+
+/// Time windows, per route or per individual trip, during which the vehicle
+/// cannot be boarded or ridden through. Windows are half-open `[start, end)`,
+/// matching the convention used for RAPTOR transfer and travel windows.
+#[derive(Debug, Clone, Default)]
+pub struct ReservedTimes {
+    per_trip: HashMap<(RouteId, usize), Vec<(Time, Time)>>,
+    per_route: HashMap<RouteId, Vec<(Time, Time)>>,
+}
+
+impl ReservedTimes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Blocks a single trip for `[start, end)`.
+    pub fn reserve_trip(&mut self, route_id: RouteId, trip_id: usize, start: Time, end: Time) {
+        self.per_trip
+            .entry((route_id, trip_id))
+            .or_default()
+            .push((start, end));
+    }
+
+    /// Blocks every trip on `route_id` for `[start, end)`, e.g. a
+    /// single-track segment shared by all trips on the route.
+    pub fn reserve_route(&mut self, route_id: RouteId, start: Time, end: Time) {
+        self.per_route.entry(route_id).or_default().push((start, end));
+    }
+
+    /// Returns the end of the first reserved window whose half-open
+    /// `[start, end)` interval overlaps the travel window
+    /// `[departure, arrival)`, if any.
+    pub(crate) fn blocking_window_end(
+        &self,
+        route_id: RouteId,
+        trip_id: usize,
+        departure: Time,
+        arrival: Time,
+    ) -> Option<Time> {
+        let windows = self
+            .per_trip
+            .get(&(route_id, trip_id))
+            .into_iter()
+            .flatten()
+            .chain(self.per_route.get(&route_id).into_iter().flatten());
+
+        windows
+            .filter(|&&(start, end)| departure < end && start < arrival)
+            .map(|&(_, end)| end)
+            .max()
+    }
+}
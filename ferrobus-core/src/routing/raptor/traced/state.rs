@@ -0,0 +1,92 @@
+use fixedbitset::FixedBitSet;
+
+use crate::routing::raptor::common::RaptorError;
+use crate::{RaptorStopId, Time};
+
+/// Backpointer describing how a stop's current-round label was reached,
+/// used by `reconstruct_journey` to walk the path from target back to source.
+#[derive(Debug, Clone, Default)]
+pub(crate) enum Predecessor {
+    #[default]
+    None,
+    /// The label is the search origin.
+    Source,
+    /// Reached by riding a transit trip from `from_stop`.
+    Transit {
+        route_id: usize,
+        trip_id: usize,
+        from_stop: RaptorStopId,
+        departure_time: Time,
+        bumped: bool,
+        /// Forced wait accumulated while the trip sat inside a reserved
+        /// (unavailable) time window, e.g. a driver break or single-track
+        /// occupancy block.
+        dwell: Time,
+    },
+    /// Reached by a walking transfer from `from_stop`.
+    Transfer {
+        from_stop: RaptorStopId,
+        departure_time: Time,
+        duration: Time,
+    },
+}
+
+/// Per-round RAPTOR labels augmented with backpointers, so the optimal
+/// journey can be reconstructed after the round loop finishes.
+#[derive(Debug)]
+pub(crate) struct TracedRaptorState {
+    pub arrival_times: Vec<Vec<Time>>,
+    pub board_times: Vec<Vec<Time>>,
+    pub marked_stops: Vec<FixedBitSet>,
+    pub best_arrival: Vec<Time>,
+    pub predecessors: Vec<Vec<Predecessor>>,
+}
+
+impl TracedRaptorState {
+    pub fn new(num_stops: usize, max_rounds: usize) -> Self {
+        TracedRaptorState {
+            arrival_times: vec![vec![Time::MAX; num_stops]; max_rounds],
+            board_times: vec![vec![Time::MAX; num_stops]; max_rounds],
+            marked_stops: (0..max_rounds)
+                .map(|_| FixedBitSet::with_capacity(num_stops))
+                .collect(),
+            best_arrival: vec![Time::MAX; num_stops],
+            predecessors: (0..max_rounds)
+                .map(|_| vec![Predecessor::None; num_stops])
+                .collect(),
+        }
+    }
+
+    /// Update a stop's label for the given round if the new arrival is strictly better.
+    /// Returns `true` only when the update also improves the stop's all-round best arrival,
+    /// i.e. when the stop should be re-marked for further relaxation.
+    pub fn update(
+        &mut self,
+        round: usize,
+        stop: usize,
+        arrival: Time,
+        board: Time,
+        predecessor: Predecessor,
+    ) -> Result<bool, RaptorError> {
+        if round >= self.arrival_times.len() || stop >= self.arrival_times[0].len() {
+            return Err(RaptorError::MaxTransfersExceeded);
+        }
+        if arrival < self.arrival_times[round][stop] {
+            self.arrival_times[round][stop] = arrival;
+            self.board_times[round][stop] = board;
+            self.predecessors[round][stop] = predecessor;
+
+            if arrival < self.best_arrival[stop] {
+                self.best_arrival[stop] = arrival;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Target pruning bound: the best arrival known so far at the target stop,
+    /// or `Time::MAX` when searching to all targets.
+    pub fn get_target_bound(&self, target: Option<RaptorStopId>) -> Time {
+        target.map_or(Time::MAX, |target_stop| self.best_arrival[target_stop])
+    }
+}
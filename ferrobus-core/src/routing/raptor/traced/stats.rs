@@ -0,0 +1,54 @@
+//! Aggregate time breakdown derived from a reconstructed [`Journey`].
+
+use hashbrown::HashMap;
+
+use super::{Journey, JourneyLeg};
+use crate::{RaptorStopId, Time};
+
+/// Summary of how a journey's total travel time splits across riding,
+/// walking, and waiting, computed by summing over its legs.
+#[derive(Debug, Clone, Default)]
+pub struct JourneyStats {
+    /// Total time spent riding transit vehicles.
+    pub in_vehicle_time: Time,
+    /// Total time spent walking, either access/egress or mid-journey
+    /// transfers.
+    pub walking_time: Time,
+    /// Total time spent waiting at stops for a boarding.
+    pub waiting_time: Time,
+    /// The single longest wait, if the journey has any.
+    pub longest_wait: Option<Time>,
+    /// Wait duration at each stop where the journey waited for a boarding.
+    pub waits_by_stop: HashMap<RaptorStopId, Time>,
+}
+
+impl Journey {
+    /// Computes the in-vehicle/walking/waiting time breakdown for this
+    /// journey by summing over its legs.
+    pub fn stats(&self) -> JourneyStats {
+        let mut stats = JourneyStats::default();
+
+        for leg in &self.legs {
+            match leg {
+                JourneyLeg::Transit {
+                    departure_time,
+                    arrival_time,
+                    ..
+                } => {
+                    stats.in_vehicle_time =
+                        stats.in_vehicle_time.saturating_add(*arrival_time - *departure_time);
+                }
+                JourneyLeg::Transfer { duration, .. } => {
+                    stats.walking_time = stats.walking_time.saturating_add(*duration);
+                }
+                JourneyLeg::Waiting { at_stop, duration } => {
+                    stats.waiting_time = stats.waiting_time.saturating_add(*duration);
+                    stats.waits_by_stop.insert(*at_stop, *duration);
+                    stats.longest_wait = Some(stats.longest_wait.map_or(*duration, |longest| longest.max(*duration)));
+                }
+            }
+        }
+
+        stats
+    }
+}
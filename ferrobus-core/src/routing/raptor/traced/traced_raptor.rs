@@ -1,11 +1,133 @@
 use fixedbitset::FixedBitSet;
+use hashbrown::HashMap;
 use itertools::Itertools;
 
+use super::reserved_times::ReservedTimes;
 use super::state::{Predecessor, TracedRaptorState};
 use crate::model::Transfer;
 use crate::routing::raptor::common::create_route_queue;
-use crate::routing::raptor::common::{RaptorError, find_earliest_trip};
-use crate::{PublicTransitData, RaptorStopId, Time};
+use crate::routing::raptor::common::{
+    AccessibilityFilter, RaptorError, RealtimeUpdate, ServiceDisruptions, find_earliest_trip,
+    min_change_time_for_stop,
+};
+use crate::{PublicTransitData, RaptorStopId, RouteId, Time};
+
+/// Starting from `trip_idx`, returns the first trip on `route_id` at
+/// `stop_idx` that isn't suspended over its ride through `stop_idx`, or
+/// `None` if every later departure on the route is also suspended.
+fn find_trip_avoiding_disruption(
+    data: &PublicTransitData,
+    route_id: RouteId,
+    stop_idx: usize,
+    mut trip_idx: usize,
+    disruptions: &ServiceDisruptions,
+) -> Option<usize> {
+    let route = &data.routes[route_id];
+    loop {
+        let stop_time = &data.stop_times[route.trips_start + trip_idx * route.num_stops + stop_idx];
+        if !disruptions.is_trip_suspended(
+            route_id,
+            trip_idx,
+            stop_time.departure,
+            stop_time.arrival,
+        ) {
+            return Some(trip_idx);
+        }
+        trip_idx += 1;
+        if trip_idx >= route.num_trips {
+            return None;
+        }
+    }
+}
+
+/// Starting from `trip_idx`, returns the first trip on `route_id` that
+/// `realtime` hasn't cancelled, or `None` if every later departure on the
+/// route was cancelled too.
+fn find_trip_avoiding_cancellation(
+    data: &PublicTransitData,
+    route_id: RouteId,
+    mut trip_idx: usize,
+    realtime: &RealtimeUpdate,
+) -> Option<usize> {
+    let route = &data.routes[route_id];
+    loop {
+        if !realtime.is_trip_cancelled(route_id, trip_idx) {
+            return Some(trip_idx);
+        }
+        trip_idx += 1;
+        if trip_idx >= route.num_trips {
+            return None;
+        }
+    }
+}
+
+/// Per-trip seat capacity used to model crowding during boarding, following
+/// the Fast-Trips capacity-constrained assignment approach.
+///
+/// A passenger who cannot fit on the first catchable trip is not dropped:
+/// they are carried over to the next trip on the same route as long as that
+/// trip departs within `bump_buffer` of the original (full) departure.
+#[derive(Debug, Clone, Copy)]
+pub struct TripCapacity {
+    /// Maximum number of boardings a single trip can accept.
+    pub seats: u32,
+    /// Slack time during which a bumped rider may still queue for the next trip.
+    pub bump_buffer: Time,
+}
+
+impl Default for TripCapacity {
+    fn default() -> Self {
+        // A few minutes of slack is enough to let a bumped rider catch the
+        // very next departure on a reasonably frequent route.
+        Self {
+            seats: u32::MAX,
+            bump_buffer: 300,
+        }
+    }
+}
+
+/// Tracks how many passengers have already been assigned to each `(route_id,
+/// trip_idx)` pair during the forward pass.
+type Occupancy = HashMap<(RouteId, usize), u32>;
+
+/// Finds the earliest trip at `stop_idx` on `route_id` that still has a free
+/// seat, starting from `trip_idx`. Returns the feasible trip index and
+/// whether at least one full trip had to be skipped (a "bump").
+fn find_trip_with_capacity(
+    data: &PublicTransitData,
+    route_id: RouteId,
+    stop_idx: usize,
+    mut trip_idx: usize,
+    capacity: &TripCapacity,
+    occupancy: &Occupancy,
+) -> Option<(usize, bool)> {
+    let route = &data.routes[route_id];
+    let mut bumped = false;
+    let original_departure =
+        data.stop_times[route.trips_start + trip_idx * route.num_stops + stop_idx].departure;
+
+    loop {
+        let seats_taken = occupancy.get(&(route_id, trip_idx)).copied().unwrap_or(0);
+        if seats_taken < capacity.seats {
+            return Some((trip_idx, bumped));
+        }
+
+        // This trip is full: try the next departure on the same route, as
+        // long as it still falls within the bump buffer.
+        let next_idx = trip_idx + 1;
+        if next_idx >= route.num_trips {
+            return None;
+        }
+        let next_departure =
+            data.stop_times[route.trips_start + next_idx * route.num_stops + stop_idx].departure;
+        if next_departure > original_departure.saturating_add(capacity.bump_buffer) {
+            return None;
+        }
+
+        trip_idx = next_idx;
+        bumped = true;
+    }
+}
 
 /// Represents a single leg of an itinerary
 #[derive(Debug, Clone)]
@@ -18,6 +140,13 @@ pub enum JourneyLeg {
         departure_time: Time,
         to_stop: RaptorStopId,
         arrival_time: Time,
+        /// `true` if the rider could not board the first catchable trip
+        /// because it was at capacity and had to wait for this later one.
+        bumped: bool,
+        /// Forced wait absorbed by this leg because the trip ran through a
+        /// reserved (unavailable) time window, already folded into
+        /// `arrival_time`.
+        dwell: Time,
     },
     /// A walking transfer between stops
     Transfer {
@@ -40,6 +169,10 @@ pub struct Journey {
     pub departure_time: Time,
     pub arrival_time: Time,
     pub transfers_count: usize,
+    /// `true` if the search was cut short by `timeout` before it could prove
+    /// optimality, so this is the best journey found so far rather than a
+    /// guaranteed earliest arrival.
+    pub provisional: bool,
 }
 
 #[allow(unused)]
@@ -48,6 +181,24 @@ pub enum TracedRaptorResult {
     AllTargets(Vec<Option<Journey>>),
 }
 
+/// Optional knobs for [`traced_raptor`] beyond the always-required
+/// `data`/`source`/`target`/`departure_time`/`max_transfers`. Grouping them
+/// here, instead of as trailing positional parameters, means a new knob is a
+/// new named field rather than another positional `None` every call site
+/// has to remember to add in the right slot.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracedRaptorOptions<'a> {
+    pub capacity: Option<TripCapacity>,
+    pub route_penalty: Option<&'a HashMap<RouteId, Time>>,
+    pub reserved_times: Option<&'a ReservedTimes>,
+    pub path_switch_bias: Option<Time>,
+    pub min_change_time: Option<Time>,
+    pub timeout: Option<std::time::Duration>,
+    pub disruptions: Option<&'a ServiceDisruptions>,
+    pub realtime: Option<&'a RealtimeUpdate>,
+    pub accessibility: Option<&'a AccessibilityFilter>,
+}
+
 #[allow(clippy::too_many_lines)]
 pub fn traced_raptor(
     data: &PublicTransitData,
@@ -55,12 +206,30 @@ pub fn traced_raptor(
     target: Option<RaptorStopId>,
     departure_time: Time,
     max_transfers: usize,
+    options: &TracedRaptorOptions<'_>,
 ) -> Result<TracedRaptorResult, RaptorError> {
+    let &TracedRaptorOptions {
+        capacity,
+        route_penalty,
+        reserved_times,
+        path_switch_bias,
+        min_change_time,
+        timeout,
+        disruptions,
+        realtime,
+        accessibility,
+    } = options;
+
     crate::routing::raptor::common::validate_raptor_inputs(data, source, target, departure_time)?;
 
     let num_stops = data.stops.len();
     let max_rounds = max_transfers + 1;
     let mut state = TracedRaptorState::new(num_stops, max_rounds);
+    let mut occupancy: Occupancy = HashMap::new();
+    let deadline = timeout.map(|d| std::time::Instant::now() + d);
+    let mut timed_out = false;
+    let switch_bias = path_switch_bias.unwrap_or(0);
+    let default_min_change_time = min_change_time.unwrap_or(0);
 
     // Initialize round 0
     state.update(
@@ -80,7 +249,23 @@ pub fn traced_raptor(
         ..
     } in transfers
     {
-        let new_time = departure_time.saturating_add(duration);
+        if let Some(disruptions) = disruptions {
+            if disruptions.is_stop_closed(target_stop)
+                || disruptions.is_transfer_blocked(source, target_stop)
+            {
+                continue;
+            }
+        }
+        if accessibility.is_some_and(|filter| !filter.stop_is_accessible(&data.stops[target_stop]))
+        {
+            continue;
+        }
+        // A transfer switches the rider off the vehicle they'd otherwise stay
+        // on, so it is biased against whenever an equally-fast alternative
+        // that avoids it exists.
+        let new_time = departure_time
+            .saturating_add(duration)
+            .saturating_add(switch_bias);
         if state.update(
             0,
             target_stop,
@@ -98,6 +283,11 @@ pub fn traced_raptor(
 
     // Main rounds
     for round in 1..max_rounds {
+        if deadline.is_some_and(|dl| std::time::Instant::now() >= dl) {
+            timed_out = true;
+            break;
+        }
+
         let prev_round = round - 1;
 
         let mut queue = create_route_queue(data, &state.marked_stops[prev_round])?;
@@ -106,6 +296,12 @@ pub fn traced_raptor(
         let target_bound = state.get_target_bound(target);
 
         while let Some((route_id, start_pos)) = queue.pop_front() {
+            if accessibility
+                .is_some_and(|filter| !filter.route_is_accessible(&data.routes[route_id]))
+            {
+                continue;
+            }
+
             let stops = data.get_route_stops(route_id)?;
 
             // Use our helper function to find earliest trip
@@ -115,42 +311,168 @@ pub fn traced_raptor(
                     route_id,
                     stops,
                     &state.board_times[prev_round],
+                    &state.arrival_times[prev_round],
+                    &state.predecessors[prev_round],
                     start_pos,
+                    default_min_change_time,
+                    disruptions,
+                    accessibility,
                 )?
             {
                 let mut trip_idx = trip_idx;
-                let mut trip = data.get_trip(route_id, trip_idx)?;
+                if let Some(disruptions) = disruptions {
+                    match find_trip_avoiding_disruption(
+                        data,
+                        route_id,
+                        current_board_pos,
+                        trip_idx,
+                        disruptions,
+                    ) {
+                        Some(feasible_idx) => trip_idx = feasible_idx,
+                        // Every catchable departure rides through a suspended window.
+                        None => continue,
+                    }
+                }
+                if let Some(realtime) = realtime {
+                    match find_trip_avoiding_cancellation(data, route_id, trip_idx, realtime) {
+                        Some(feasible_idx) => trip_idx = feasible_idx,
+                        // Every catchable departure was cancelled.
+                        None => continue,
+                    }
+                }
+                let mut bumped = false;
+                if let Some(cap) = &capacity {
+                    match find_trip_with_capacity(
+                        data,
+                        route_id,
+                        current_board_pos,
+                        trip_idx,
+                        cap,
+                        &occupancy,
+                    ) {
+                        Some((feasible_idx, was_bumped)) => {
+                            trip_idx = feasible_idx;
+                            bumped = was_bumped;
+                        }
+                        // Every departure within the bump buffer is full: no boarding here.
+                        None => continue,
+                    }
+                    *occupancy.entry((route_id, trip_idx)).or_insert(0) += 1;
+                }
+
+                let mut trip = data.get_trip_with_realtime(route_id, trip_idx, realtime)?;
                 let mut boarding_stop = boarding_stop;
-                let mut boarding_time = boarding_time;
+                let mut boarding_time = trip[current_board_pos].departure;
+                let mut dwell: Time = 0;
 
                 // Process remaining stops in this route
                 for (trip_stop_idx, &stop) in stops.iter().enumerate().skip(current_board_pos) {
+                    // A closed stop can neither be boarded at nor alighted from.
+                    if disruptions.is_some_and(|d| d.is_stop_closed(stop)) {
+                        continue;
+                    }
+                    if accessibility
+                        .is_some_and(|filter| !filter.stop_is_accessible(&data.stops[stop]))
+                    {
+                        continue;
+                    }
+
                     // Check if we can "upgrade" to an earlier trip
                     let prev_board = state.board_times[prev_round][stop];
                     if prev_board < trip[trip_stop_idx].departure {
-                        if let Some(new_trip_idx) =
+                        if let Some(mut new_trip_idx) =
                             find_earliest_trip(data, route_id, trip_stop_idx, prev_board)
                         {
+                            if let Some(disruptions) = disruptions {
+                                new_trip_idx = find_trip_avoiding_disruption(
+                                    data,
+                                    route_id,
+                                    trip_stop_idx,
+                                    new_trip_idx,
+                                    disruptions,
+                                )
+                                .unwrap_or(trip_idx);
+                            }
+                            if let Some(realtime) = realtime {
+                                new_trip_idx = find_trip_avoiding_cancellation(
+                                    data,
+                                    route_id,
+                                    new_trip_idx,
+                                    realtime,
+                                )
+                                .unwrap_or(trip_idx);
+                            }
+                            let mut new_bumped = false;
+                            if let Some(cap) = &capacity {
+                                match find_trip_with_capacity(
+                                    data,
+                                    route_id,
+                                    trip_stop_idx,
+                                    new_trip_idx,
+                                    cap,
+                                    &occupancy,
+                                ) {
+                                    Some((feasible_idx, was_bumped)) => {
+                                        new_trip_idx = feasible_idx;
+                                        new_bumped = was_bumped;
+                                    }
+                                    None => new_trip_idx = trip_idx,
+                                }
+                            }
                             if new_trip_idx != trip_idx {
+                                if let Some(_cap) = &capacity {
+                                    if let Some(seats) = occupancy.get_mut(&(route_id, trip_idx)) {
+                                        *seats = seats.saturating_sub(1);
+                                    }
+                                    *occupancy.entry((route_id, new_trip_idx)).or_insert(0) += 1;
+                                }
                                 trip_idx = new_trip_idx;
-                                trip = data.get_trip(route_id, new_trip_idx)?;
+                                trip =
+                                    data.get_trip_with_realtime(route_id, new_trip_idx, realtime)?;
                                 boarding_stop = stop;
                                 boarding_time = trip[trip_stop_idx].departure;
+                                bumped = new_bumped;
+                                dwell = 0;
                             }
                         }
                     }
 
-                    let actual_arrival = trip[trip_stop_idx].arrival;
+                    // If this hop runs through a reserved (unavailable) window, the
+                    // vehicle is forced to wait until the window clears; carry that
+                    // wait forward onto every later stop of this same trip.
+                    if let Some(reserved) = reserved_times {
+                        let delayed_arrival = trip[trip_stop_idx].arrival.saturating_add(dwell);
+                        if let Some(window_end) = reserved.blocking_window_end(
+                            route_id,
+                            trip_idx,
+                            boarding_time,
+                            delayed_arrival,
+                        ) {
+                            dwell =
+                                dwell.saturating_add(window_end.saturating_sub(delayed_arrival));
+                        }
+                    }
+
+                    let actual_arrival = trip[trip_stop_idx].arrival.saturating_add(dwell);
                     let effective_board = if let Some(target_stop) = target {
                         if stop == target_stop {
                             actual_arrival
                         } else {
-                            trip[trip_stop_idx].departure
+                            trip[trip_stop_idx].departure.saturating_add(dwell)
                         }
                     } else {
-                        trip[trip_stop_idx].departure
+                        trip[trip_stop_idx].departure.saturating_add(dwell)
                     };
 
+                    // Steer subsequent path-set passes away from routes already used by
+                    // earlier journeys by inflating their labels with a fixed surcharge.
+                    let surcharge = route_penalty
+                        .and_then(|penalties| penalties.get(&route_id))
+                        .copied()
+                        .unwrap_or(0);
+                    let actual_arrival = actual_arrival.saturating_add(surcharge);
+                    let effective_board = effective_board.saturating_add(surcharge);
+
                     // Record the trip we took to get here
                     if state.update(
                         round,
@@ -162,6 +484,8 @@ pub fn traced_raptor(
                             trip_id: trip_idx,
                             from_stop: boarding_stop,
                             departure_time: boarding_time,
+                            bumped,
+                            dwell,
                         },
                     )? {
                         state.marked_stops[round].set(stop, true);
@@ -175,14 +499,24 @@ pub fn traced_raptor(
         }
 
         // Process footpaths for this round
-        let new_marks = process_detailed_foot_paths(data, target, num_stops, &mut state, round)?;
+        let new_marks = process_detailed_foot_paths(
+            data,
+            target,
+            num_stops,
+            &mut state,
+            round,
+            switch_bias,
+            disruptions,
+            accessibility,
+        )?;
         state.marked_stops[round].union_with(&new_marks);
 
         // Check if we can terminate early
         if let Some(target_stop) = target {
             let arrival_time = state.arrival_times[round][target_stop];
             if arrival_time != Time::MAX && arrival_time > state.best_arrival[target_stop] {
-                let journey = reconstruct_journey(data, &state, source, target_stop)?;
+                let journey =
+                    reconstruct_journey(data, &state, source, target_stop, false, realtime)?;
                 return Ok(TracedRaptorResult::SingleTarget(Some(journey)));
             }
         }
@@ -198,7 +532,14 @@ pub fn traced_raptor(
         let journey = if state.best_arrival[target_stop] == Time::MAX {
             None
         } else {
-            Some(reconstruct_journey(data, &state, source, target_stop)?)
+            Some(reconstruct_journey(
+                data,
+                &state,
+                source,
+                target_stop,
+                timed_out,
+                realtime,
+            )?)
         };
         Ok(TracedRaptorResult::SingleTarget(journey))
     } else {
@@ -206,7 +547,9 @@ pub fn traced_raptor(
         #[allow(clippy::needless_range_loop)]
         for stop in 0..num_stops {
             if state.best_arrival[stop] != Time::MAX {
-                journeys[stop] = Some(reconstruct_journey(data, &state, source, stop)?);
+                journeys[stop] = Some(reconstruct_journey(
+                    data, &state, source, stop, timed_out, realtime,
+                )?);
             }
         }
         Ok(TracedRaptorResult::AllTargets(journeys))
@@ -219,6 +562,9 @@ fn process_detailed_foot_paths(
     num_stops: usize,
     state: &mut TracedRaptorState,
     round: usize,
+    switch_bias: Time,
+    disruptions: Option<&ServiceDisruptions>,
+    accessibility: Option<&AccessibilityFilter>,
 ) -> Result<FixedBitSet, RaptorError> {
     let current_marks: Vec<RaptorStopId> = state.marked_stops[round].ones().collect();
     let mut new_marks = FixedBitSet::with_capacity(num_stops);
@@ -237,7 +583,23 @@ fn process_detailed_foot_paths(
             ..
         } in transfers
         {
-            let new_time = current_board.saturating_add(duration);
+            if let Some(disruptions) = disruptions {
+                if disruptions.is_stop_closed(target_stop)
+                    || disruptions.is_transfer_blocked(stop, target_stop)
+                {
+                    continue;
+                }
+            }
+            if accessibility
+                .is_some_and(|filter| !filter.stop_is_accessible(&data.stops[target_stop]))
+            {
+                continue;
+            }
+            // Biased like the source transfers above: a switch away from the
+            // current vehicle only wins ties, it never wins outright.
+            let new_time = current_board
+                .saturating_add(duration)
+                .saturating_add(switch_bias);
             if new_time >= state.board_times[round][target_stop] || new_time >= target_bound {
                 continue;
             }
@@ -267,6 +629,8 @@ fn reconstruct_journey(
     state: &TracedRaptorState,
     source: RaptorStopId,
     target: RaptorStopId,
+    provisional: bool,
+    realtime: Option<&RealtimeUpdate>,
 ) -> Result<Journey, RaptorError> {
     let mut legs = Vec::new();
     let mut current_stop = target;
@@ -280,8 +644,6 @@ fn reconstruct_journey(
         }
     }
 
-    let arrival_time = state.best_arrival[target];
-
     // Backtrack from target to source
     while current_stop != source {
         match &state.predecessors[current_round][current_stop] {
@@ -297,8 +659,10 @@ fn reconstruct_journey(
                 trip_id,
                 from_stop,
                 departure_time,
+                bumped,
+                dwell,
             } => {
-                let trip = data.get_trip(*route_id, *trip_id)?;
+                let trip = data.get_trip_with_realtime(*route_id, *trip_id, realtime)?;
                 let stops = data.get_route_stops(*route_id)?;
 
                 // Find the indices in the trip
@@ -313,7 +677,9 @@ fn reconstruct_journey(
                     from_stop: *from_stop,
                     departure_time: *departure_time,
                     to_stop: current_stop,
-                    arrival_time: trip[to_idx].arrival,
+                    arrival_time: trip[to_idx].arrival.saturating_add(*dwell),
+                    bumped: *bumped,
+                    dwell: *dwell,
                 });
 
                 // Move to previous stop and round
@@ -376,32 +742,78 @@ fn reconstruct_journey(
         .filter(|leg| matches!(leg, JourneyLeg::Transfer { .. }))
         .count();
 
+    // `state.best_arrival` may be inflated by `route_penalty`/`path_switch_bias`
+    // search-steering surcharges (see the round loop above): those exist only
+    // to bias which label wins a comparison, not to change what actually
+    // happened on the ground. The true arrival is whatever the last leg's own
+    // (unbiased) schedule-derived `arrival_time` says; a `Waiting` leg is
+    // never last (it's only inserted between two real legs), so this always
+    // finds a real one when there are any legs at all.
+    let arrival_time = legs
+        .last()
+        .map(|leg| match leg {
+            JourneyLeg::Transit { arrival_time, .. } | JourneyLeg::Transfer { arrival_time, .. } => {
+                *arrival_time
+            }
+            JourneyLeg::Waiting { .. } => unreachable!("a waiting leg is never last"),
+        })
+        .unwrap_or_else(|| state.board_times[0][source]);
+
     Ok(Journey {
         legs,
         departure_time: state.board_times[0][source],
         arrival_time,
         transfers_count,
+        provisional,
     })
 }
 
 /// Find the earliest trip at a given stop on a route for traced implementation
 /// Returns (`trip_idx``board_pos`, `boarding_stop`, `boarding_time`) if found, None otherwise
+///
+/// A rider whose current label at `stop` came from alighting a *different*
+/// route must wait out that stop's platform-change buffer
+/// (`default_min_change_time`, overridable per-stop) before the search will
+/// consider them boarded; a rider continuing on the same route, or one who
+/// arrived by transfer or as the search origin, is unaffected.
+#[allow(clippy::too_many_arguments)]
 fn find_traced_trip_at_stop(
     data: &PublicTransitData,
     route_id: usize,
     stops: &[usize],
     board_times: &[Time],
+    arrival_times: &[Time],
+    predecessors: &[Predecessor],
     start_pos: usize,
+    default_min_change_time: Time,
+    disruptions: Option<&ServiceDisruptions>,
+    accessibility: Option<&AccessibilityFilter>,
 ) -> Result<Option<(usize, usize, usize, Time)>, RaptorError> {
     let mut current_trip_opt = None;
     let mut current_board_pos = 0;
 
     // Find the earliest trip on this route that is catchable
     for (idx, &stop) in stops.iter().enumerate().skip(start_pos) {
-        let earliest_board = board_times[stop];
-        if earliest_board == Time::MAX {
+        if disruptions.is_some_and(|d| d.is_stop_closed(stop)) {
+            continue;
+        }
+        if accessibility.is_some_and(|filter| !filter.stop_is_accessible(&data.stops[stop])) {
+            continue;
+        }
+        let board_time = board_times[stop];
+        if board_time == Time::MAX {
             continue;
         }
+        let earliest_board = match &predecessors[stop] {
+            Predecessor::Transit {
+                route_id: prev_route,
+                ..
+            } if *prev_route != route_id => {
+                let buffer = min_change_time_for_stop(data, stop, default_min_change_time);
+                board_time.max(arrival_times[stop].saturating_add(buffer))
+            }
+            _ => board_time,
+        };
         if let Some(trip_idx) = find_earliest_trip(data, route_id, idx, earliest_board) {
             let trip = data.get_trip(route_id, trip_idx)?;
             current_trip_opt = Some((trip_idx, idx, stop, trip[idx].departure));
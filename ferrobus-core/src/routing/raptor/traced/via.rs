@@ -0,0 +1,373 @@
+//! Via-waypoint routing: find a journey from `source` to `target` that is
+//! required to pass through a set of intermediate stops, either in whichever
+//! order minimizes the final arrival time (`traced_raptor_via`) or in an
+//! order the caller already knows (`traced_raptor_via_ordered`).
+
+use std::cell::RefCell;
+
+use crate::algo::held_karp::held_karp_with_finish;
+use crate::routing::raptor::common::RaptorError;
+use crate::{PublicTransitData, RaptorStopId, Time};
+
+use super::{Journey, TracedRaptorOptions, TracedRaptorResult, traced_raptor};
+
+/// Above this many waypoints, the Held-Karp dynamic program below becomes
+/// impractical, so a greedy nearest-by-arrival-time heuristic is used
+/// instead. Unlike the permutation enumeration this replaces (`n!` full
+/// re-routes per query), Held-Karp's `O(2^n * n^2)` table visits each
+/// (subset, last-stop) pair once, the same trade-off `routing::tour` makes
+/// for the free-start multi-waypoint tour problem.
+const MAX_EXACT_WAYPOINTS: usize = 12;
+
+/// Plans a journey from `source` to `target` that must visit every stop in
+/// `waypoints`, in an order chosen to minimize the final arrival time.
+///
+/// Internally this chains per-segment `traced_raptor` runs: each segment
+/// departs at the previous segment's arrival time, and the resulting legs are
+/// concatenated into a single monotonic itinerary.
+pub fn traced_raptor_via(
+    data: &PublicTransitData,
+    source: RaptorStopId,
+    waypoints: &[RaptorStopId],
+    target: RaptorStopId,
+    departure_time: Time,
+    max_transfers: usize,
+) -> Result<Option<Journey>, RaptorError> {
+    if waypoints.is_empty() {
+        return run_segment(data, source, target, departure_time, max_transfers);
+    }
+
+    if waypoints.len() <= MAX_EXACT_WAYPOINTS {
+        best_by_held_karp(
+            data,
+            source,
+            waypoints,
+            target,
+            departure_time,
+            max_transfers,
+        )
+    } else {
+        best_by_greedy_insertion(
+            data,
+            source,
+            waypoints,
+            target,
+            departure_time,
+            max_transfers,
+        )
+    }
+}
+
+/// Plans a journey from `source` to `target` that must visit `waypoints` in
+/// the exact order given, stitching one `traced_raptor` segment per hop:
+/// each segment departs at the previous segment's arrival time. Unlike
+/// [`traced_raptor_via`], the caller is asserting the visiting order
+/// themselves rather than asking for it to be optimized.
+pub fn traced_raptor_via_ordered(
+    data: &PublicTransitData,
+    source: RaptorStopId,
+    waypoints: &[RaptorStopId],
+    target: RaptorStopId,
+    departure_time: Time,
+    max_transfers: usize,
+) -> Result<Option<Journey>, RaptorError> {
+    chain_stops_in_order(
+        data,
+        source,
+        waypoints,
+        target,
+        departure_time,
+        max_transfers,
+        None,
+    )
+}
+
+/// Held-Karp dynamic programming over the waypoints' visiting order, via
+/// [`crate::algo::held_karp`]'s generic subset-DP helper. Each hop re-routes
+/// with [`run_segment`], so the final hop to `target` is supplied as a
+/// "finish" cost: `target` is fixed rather than itself a waypoint to choose
+/// an order over, and every subset's best last-waypoint is evaluated against
+/// it before the overall best is picked.
+///
+/// [`run_segment`] can itself fail; since the generic helper's callbacks can
+/// only return `None` for "unreachable", any [`RaptorError`] is stashed in
+/// `error` as it's encountered and propagated once the DP finishes.
+fn best_by_held_karp(
+    data: &PublicTransitData,
+    source: RaptorStopId,
+    waypoints: &[RaptorStopId],
+    target: RaptorStopId,
+    departure_time: Time,
+    max_transfers: usize,
+) -> Result<Option<Journey>, RaptorError> {
+    let n = waypoints.len();
+
+    let error = RefCell::new(None);
+    let segment_to = |from: RaptorStopId, to: RaptorStopId, clock: Time| {
+        if error.borrow().is_some() {
+            return None;
+        }
+        match run_segment(data, from, to, clock, max_transfers) {
+            Ok(segment) => segment.map(|segment| (segment.arrival_time, segment)),
+            Err(err) => {
+                *error.borrow_mut() = Some(err);
+                None
+            }
+        }
+    };
+
+    let result = held_karp_with_finish(
+        n,
+        |j| segment_to(source, waypoints[j], departure_time),
+        |j, arrival_at_j, k| segment_to(waypoints[j], waypoints[k], arrival_at_j),
+        |j, arrival_at_j| segment_to(waypoints[j], target, arrival_at_j),
+    );
+
+    if let Some(err) = error.into_inner() {
+        return Err(err);
+    }
+
+    let Some((_, segments, _)) = result else {
+        return Ok(None);
+    };
+
+    let arrival_time = segments.last().unwrap().arrival_time;
+    let mut legs = Vec::new();
+    let mut transfers_count = 0;
+    for segment in segments {
+        transfers_count += segment.transfers_count;
+        legs.extend(segment.legs);
+    }
+
+    Ok(Some(Journey {
+        legs,
+        departure_time,
+        arrival_time,
+        transfers_count,
+        provisional: false,
+    }))
+}
+
+/// Chains one `traced_raptor` segment per hop `source -> waypoints[0] ->
+/// ... -> target`, in the given order, bailing out early (returning `Ok(None)`)
+/// as soon as either a segment is unreachable or the running arrival time
+/// already meets or exceeds `prune_bound` (the best complete journey found so
+/// far by the caller, if any).
+fn chain_stops_in_order(
+    data: &PublicTransitData,
+    source: RaptorStopId,
+    stop_order: &[RaptorStopId],
+    target: RaptorStopId,
+    departure_time: Time,
+    max_transfers: usize,
+    prune_bound: Option<Time>,
+) -> Result<Option<Journey>, RaptorError> {
+    let mut legs = Vec::new();
+    let mut current_stop = source;
+    let mut current_time = departure_time;
+    let mut transfers_count = 0;
+
+    for &waypoint in stop_order {
+        if prune_bound.is_some_and(|bound| current_time >= bound) {
+            return Ok(None);
+        }
+        let Some(segment) = run_segment(data, current_stop, waypoint, current_time, max_transfers)?
+        else {
+            return Ok(None);
+        };
+        current_time = segment.arrival_time;
+        transfers_count += segment.transfers_count;
+        legs.extend(segment.legs);
+        current_stop = waypoint;
+    }
+
+    let Some(last_segment) = run_segment(data, current_stop, target, current_time, max_transfers)?
+    else {
+        return Ok(None);
+    };
+
+    transfers_count += last_segment.transfers_count;
+    legs.extend(last_segment.legs);
+
+    Ok(Some(Journey {
+        legs,
+        departure_time,
+        arrival_time: last_segment.arrival_time,
+        transfers_count,
+        provisional: false,
+    }))
+}
+
+/// Greedy nearest-by-arrival-time insertion followed by 2-opt refinement,
+/// used once the number of waypoints makes exhaustive permutation
+/// impractical. Mirrors the construction-then-refinement split
+/// `routing::tour::traced_multimodal_tour` and `optimal_tour` both use
+/// around their own `held_karp`/`held_karp_with_finish`-based exact solvers.
+fn best_by_greedy_insertion(
+    data: &PublicTransitData,
+    source: RaptorStopId,
+    waypoints: &[RaptorStopId],
+    target: RaptorStopId,
+    departure_time: Time,
+    max_transfers: usize,
+) -> Result<Option<Journey>, RaptorError> {
+    let Some(order) = nearest_neighbor_order(
+        data,
+        source,
+        waypoints,
+        departure_time,
+        max_transfers,
+    )?
+    else {
+        return Ok(None);
+    };
+    let order = two_opt(
+        data,
+        source,
+        order,
+        target,
+        departure_time,
+        max_transfers,
+    )?;
+
+    chain_stops_in_order(
+        data,
+        source,
+        &order,
+        target,
+        departure_time,
+        max_transfers,
+        None,
+    )
+}
+
+/// Builds an initial visiting order over `waypoints` by always riding to
+/// whichever unvisited one the current stop/time reaches earliest. Returns
+/// `None` if some waypoint is never reachable from the others.
+fn nearest_neighbor_order(
+    data: &PublicTransitData,
+    source: RaptorStopId,
+    waypoints: &[RaptorStopId],
+    departure_time: Time,
+    max_transfers: usize,
+) -> Result<Option<Vec<RaptorStopId>>, RaptorError> {
+    let mut remaining: Vec<RaptorStopId> = waypoints.to_vec();
+    let mut order = Vec::with_capacity(waypoints.len());
+    let mut current_stop = source;
+    let mut current_time = departure_time;
+
+    while !remaining.is_empty() {
+        let mut nearest: Option<(usize, Time)> = None;
+
+        for (idx, &candidate_stop) in remaining.iter().enumerate() {
+            if let Some(segment) = run_segment(
+                data,
+                current_stop,
+                candidate_stop,
+                current_time,
+                max_transfers,
+            )? && nearest.is_none_or(|(_, best)| segment.arrival_time < best)
+            {
+                nearest = Some((idx, segment.arrival_time));
+            }
+        }
+
+        let Some((idx, arrival)) = nearest else {
+            // No remaining waypoint is reachable at all.
+            return Ok(None);
+        };
+
+        current_time = arrival;
+        current_stop = remaining.swap_remove(idx);
+        order.push(current_stop);
+    }
+
+    Ok(Some(order))
+}
+
+/// Repeatedly reverses a segment of `order` when doing so lowers the
+/// complete `source -> order -> target` journey's final arrival time.
+fn two_opt(
+    data: &PublicTransitData,
+    source: RaptorStopId,
+    mut order: Vec<RaptorStopId>,
+    target: RaptorStopId,
+    departure_time: Time,
+    max_transfers: usize,
+) -> Result<Vec<RaptorStopId>, RaptorError> {
+    let n = order.len();
+    loop {
+        let mut improved = false;
+        let Some(mut best_arrival) = chain_arrival(
+            data,
+            source,
+            &order,
+            target,
+            departure_time,
+            max_transfers,
+        )?
+        else {
+            return Ok(order);
+        };
+
+        for i in 0..n.saturating_sub(1) {
+            for j in (i + 1)..n {
+                let mut candidate = order.clone();
+                candidate[i..=j].reverse();
+                if let Some(candidate_arrival) = chain_arrival(
+                    data,
+                    source,
+                    &candidate,
+                    target,
+                    departure_time,
+                    max_transfers,
+                )? && candidate_arrival < best_arrival
+                {
+                    order = candidate;
+                    best_arrival = candidate_arrival;
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    Ok(order)
+}
+
+/// The final arrival time of `source -> stop_order -> target`, or `None` if
+/// some consecutive pair is unreachable.
+fn chain_arrival(
+    data: &PublicTransitData,
+    source: RaptorStopId,
+    stop_order: &[RaptorStopId],
+    target: RaptorStopId,
+    departure_time: Time,
+    max_transfers: usize,
+) -> Result<Option<Time>, RaptorError> {
+    Ok(
+        chain_stops_in_order(data, source, stop_order, target, departure_time, max_transfers, None)?
+            .map(|journey| journey.arrival_time),
+    )
+}
+
+fn run_segment(
+    data: &PublicTransitData,
+    from: RaptorStopId,
+    to: RaptorStopId,
+    departure_time: Time,
+    max_transfers: usize,
+) -> Result<Option<Journey>, RaptorError> {
+    match traced_raptor(
+        data,
+        from,
+        Some(to),
+        departure_time,
+        max_transfers,
+        &TracedRaptorOptions::default(),
+    )? {
+        TracedRaptorResult::SingleTarget(journey) => Ok(journey),
+        TracedRaptorResult::AllTargets(_) => unreachable!("single target was requested"),
+    }
+}
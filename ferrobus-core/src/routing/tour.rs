@@ -0,0 +1,289 @@
+//! Multi-waypoint tour routing: finds the best order to visit a set of
+//! points reachable by transit, rather than a single origin -> destination
+//! query.
+//!
+//! Unlike a plain traveling-salesman problem, the cost of each leg is not a
+//! fixed number: boarding a trip at 08:00 and boarding the "same" leg at
+//! 08:45 can reach different stops on different routes, so a cost matrix
+//! computed once up front would silently go stale as the tour progresses.
+//! Every transition here is therefore routed with the clock the tour has
+//! actually reached by that point.
+
+use std::cell::RefCell;
+
+use crate::{
+    algo::held_karp::held_karp,
+    model::TransitPoint,
+    routing::{
+        detailed_itinerary::{traced_multimodal_routing, DetailedJourney},
+        raptor::RealtimeUpdate,
+    },
+    Error, Time, TransitModel,
+};
+
+/// Instances at or below this many waypoints are solved exactly with
+/// Held-Karp; larger instances fall back to nearest-neighbor + 2-opt, since
+/// each Held-Karp transition re-routes rather than looking up a matrix cell,
+/// making the `O(2^n * n^2)` table far more expensive per-state than
+/// `optimal_tour`'s static version.
+const HELD_KARP_MAX_WAYPOINTS: usize = 12;
+
+/// Routes from `from` to `to` departing at `departure_time`, returning the
+/// arrival time alongside the journey that achieves it.
+fn cost_at_time(
+    transit_model: &TransitModel,
+    from: &TransitPoint,
+    to: &TransitPoint,
+    departure_time: Time,
+    max_transfers: usize,
+    realtime: Option<&RealtimeUpdate>,
+) -> Result<Option<(Time, DetailedJourney)>, Error> {
+    let journey = traced_multimodal_routing(
+        transit_model,
+        from,
+        to,
+        departure_time,
+        max_transfers,
+        realtime,
+    )?;
+    Ok(journey.map(|journey| (journey.arrival_time, journey)))
+}
+
+/// Routes `order` (indices into `waypoints`) back-to-back starting at
+/// `departure_time`, threading each leg's arrival time into the next leg's
+/// departure. Returns the legs in visiting order and the final arrival
+/// time, or `None` if some consecutive pair is unreachable.
+fn evaluate_order(
+    transit_model: &TransitModel,
+    waypoints: &[TransitPoint],
+    order: &[usize],
+    departure_time: Time,
+    max_transfers: usize,
+    realtime: Option<&RealtimeUpdate>,
+) -> Result<Option<(Vec<DetailedJourney>, Time)>, Error> {
+    let mut legs = Vec::with_capacity(order.len().saturating_sub(1));
+    let mut clock = departure_time;
+
+    for pair in order.windows(2) {
+        let Some((arrival, journey)) = cost_at_time(
+            transit_model,
+            &waypoints[pair[0]],
+            &waypoints[pair[1]],
+            clock,
+            max_transfers,
+            realtime,
+        )?
+        else {
+            return Ok(None);
+        };
+        clock = arrival;
+        legs.push(journey);
+    }
+
+    Ok(Some((legs, clock)))
+}
+
+/// Held-Karp dynamic programming over a time-dependent cost function, via
+/// [`crate::algo::held_karp`]'s generic subset-DP helper.
+///
+/// Each hop re-routes with [`cost_at_time`], which can itself fail (e.g. a
+/// timed-out RAPTOR search); since the generic helper's callbacks can only
+/// return `None` for "unreachable", any routing [`Error`] is stashed in
+/// `error` as it's encountered and propagated once the DP finishes.
+fn held_karp_tour(
+    transit_model: &TransitModel,
+    waypoints: &[TransitPoint],
+    departure_time: Time,
+    max_transfers: usize,
+    realtime: Option<&RealtimeUpdate>,
+) -> Result<Option<Vec<DetailedJourney>>, Error> {
+    let n = waypoints.len();
+    if n <= 1 {
+        return Ok(Some(Vec::new()));
+    }
+    let rest = n - 1;
+
+    let error = RefCell::new(None);
+    let route = |from: &TransitPoint, to: &TransitPoint, clock: Time| {
+        if error.borrow().is_some() {
+            return None;
+        }
+        match cost_at_time(transit_model, from, to, clock, max_transfers, realtime) {
+            Ok(result) => result,
+            Err(err) => {
+                *error.borrow_mut() = Some(err);
+                None
+            }
+        }
+    };
+
+    let result = held_karp(
+        rest,
+        |j| route(&waypoints[0], &waypoints[j + 1], departure_time),
+        |j, arrival_at_j, k| route(&waypoints[j + 1], &waypoints[k + 1], arrival_at_j),
+    );
+
+    if let Some(err) = error.into_inner() {
+        return Err(err);
+    }
+
+    Ok(result.map(|(_, legs, _)| legs))
+}
+
+/// Builds an initial visiting order by always moving to whichever
+/// unvisited waypoint the current clock reaches soonest.
+fn nearest_neighbor_order(
+    transit_model: &TransitModel,
+    waypoints: &[TransitPoint],
+    departure_time: Time,
+    max_transfers: usize,
+    realtime: Option<&RealtimeUpdate>,
+) -> Result<Option<Vec<usize>>, Error> {
+    let n = waypoints.len();
+    let mut visited = vec![false; n];
+    let mut order = vec![0];
+    visited[0] = true;
+    let mut clock = departure_time;
+
+    for _ in 1..n {
+        let current = *order.last().unwrap();
+        let mut best: Option<(usize, Time)> = None;
+        for next in (0..n).filter(|&j| !visited[j]) {
+            if let Some((arrival, _)) = cost_at_time(
+                transit_model,
+                &waypoints[current],
+                &waypoints[next],
+                clock,
+                max_transfers,
+                realtime,
+            )? {
+                if best.is_none_or(|(_, best_arrival)| arrival < best_arrival) {
+                    best = Some((next, arrival));
+                }
+            }
+        }
+        let Some((next, arrival)) = best else {
+            return Ok(None);
+        };
+        visited[next] = true;
+        order.push(next);
+        clock = arrival;
+    }
+
+    Ok(Some(order))
+}
+
+/// Repeatedly reverses a segment of `order` when doing so lowers the tour's
+/// final arrival time. Each candidate reversal re-routes its whole suffix
+/// from the reversal point on, since a later leg's feasible trips depend on
+/// exactly when the tour reaches it.
+fn two_opt(
+    transit_model: &TransitModel,
+    waypoints: &[TransitPoint],
+    mut order: Vec<usize>,
+    departure_time: Time,
+    max_transfers: usize,
+    realtime: Option<&RealtimeUpdate>,
+) -> Result<Vec<usize>, Error> {
+    let n = order.len();
+    loop {
+        let mut improved = false;
+        let Some((_, mut best_arrival)) = evaluate_order(
+            transit_model,
+            waypoints,
+            &order,
+            departure_time,
+            max_transfers,
+            realtime,
+        )?
+        else {
+            return Ok(order);
+        };
+
+        for i in 1..n.saturating_sub(1) {
+            for j in (i + 1)..n {
+                let mut candidate = order.clone();
+                candidate[i..=j].reverse();
+                if let Some((_, candidate_arrival)) = evaluate_order(
+                    transit_model,
+                    waypoints,
+                    &candidate,
+                    departure_time,
+                    max_transfers,
+                    realtime,
+                )? {
+                    if candidate_arrival < best_arrival {
+                        order = candidate;
+                        best_arrival = candidate_arrival;
+                        improved = true;
+                    }
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    Ok(order)
+}
+
+/// Finds the best order to visit `waypoints` by transit and returns the
+/// resulting journeys in visiting order, one [`DetailedJourney`] per leg.
+///
+/// `waypoints[0]` is fixed as the tour's start; every other waypoint is
+/// visited exactly once and the tour does not return to the start. At most
+/// [`HELD_KARP_MAX_WAYPOINTS`] waypoints are solved exactly with
+/// time-dependent Held-Karp; larger instances fall back to a
+/// nearest-neighbor construction refined with 2-opt.
+///
+/// Concatenating `DetailedJourney::to_geojson`'s features across the
+/// returned `Vec`, in order, yields the full tour as one `FeatureCollection`.
+///
+/// Returns `Ok(None)` if no complete tour exists because some waypoint is
+/// unreachable from the others at the time the tour would reach it.
+pub fn traced_multimodal_tour(
+    transit_model: &TransitModel,
+    waypoints: &[TransitPoint],
+    departure_time: Time,
+    max_transfers: usize,
+    realtime: Option<&RealtimeUpdate>,
+) -> Result<Option<Vec<DetailedJourney>>, Error> {
+    if waypoints.len() <= HELD_KARP_MAX_WAYPOINTS {
+        return held_karp_tour(
+            transit_model,
+            waypoints,
+            departure_time,
+            max_transfers,
+            realtime,
+        );
+    }
+
+    let Some(order) = nearest_neighbor_order(
+        transit_model,
+        waypoints,
+        departure_time,
+        max_transfers,
+        realtime,
+    )?
+    else {
+        return Ok(None);
+    };
+    let order = two_opt(
+        transit_model,
+        waypoints,
+        order,
+        departure_time,
+        max_transfers,
+        realtime,
+    )?;
+
+    Ok(evaluate_order(
+        transit_model,
+        waypoints,
+        &order,
+        departure_time,
+        max_transfers,
+        realtime,
+    )?
+    .map(|(legs, _)| legs))
+}
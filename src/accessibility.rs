@@ -0,0 +1,140 @@
+use ferrobus_core::prelude::*;
+use pyo3::prelude::*;
+use pyo3_stub_gen::derive::gen_stub_pyfunction;
+use rayon::prelude::*;
+
+use crate::model::PyTransitModel;
+use crate::routing::PyTransitPoint;
+
+/// Computes a cumulative-opportunity accessibility score for one origin:
+/// one value per cutoff (the summed weight of every destination reached
+/// within that cutoff), followed by the exponentially-decayed score if
+/// `decay_beta` is set.
+fn score_origin(
+    travel_times: &[Option<MultiModalResult>],
+    weights: &[f64],
+    cutoffs: &[Time],
+    decay_beta: Option<f64>,
+) -> Vec<f64> {
+    let mut scores = vec![0.0; cutoffs.len() + usize::from(decay_beta.is_some())];
+
+    for (result, &weight) in travel_times.iter().zip(weights) {
+        let Some(travel_time) = result.as_ref().map(|r| r.travel_time) else {
+            continue;
+        };
+
+        for (idx, &cutoff) in cutoffs.iter().enumerate() {
+            if travel_time <= cutoff {
+                scores[idx] += weight;
+            }
+        }
+
+        if let Some(beta) = decay_beta {
+            let decay = (-beta * f64::from(travel_time)).exp();
+            if let Some(decayed_score) = scores.last_mut() {
+                *decayed_score += weight * decay;
+            }
+        }
+    }
+
+    scores
+}
+
+/// Compute cumulative-opportunity accessibility scores for a set of origins
+///
+/// For each origin, runs a one-to-many multimodal routing search against
+/// `destinations` and aggregates the results into accessibility scores: the
+/// summed `weights` of every destination reached within each of `cutoffs`,
+/// plus (if `decay_beta` is given) a continuous score where each
+/// destination's weight is discounted by `exp(-decay_beta * travel_time)`
+/// instead of a hard cutoff.
+///
+/// Parameters
+/// ----------
+/// transit_model : TransitModel
+///     The transit model to use for routing.
+/// origins : list[TransitPoint]
+///     Points to compute an accessibility score for.
+/// destinations : list[TransitPoint]
+///     Points representing opportunities (jobs, population, ...).
+/// weights : list[float]
+///     Opportunity weight of each destination, same length as `destinations`.
+/// departure_time : int
+///     Time of departure in seconds since midnight.
+/// max_transfers : int, default=3
+///     Maximum number of transfers allowed in route planning.
+/// cutoffs : list[int], default=[]
+///     Travel-time cutoffs, in seconds, to aggregate reachable weight over.
+/// decay_beta : float, optional
+///     If given, also compute a continuous `exp(-decay_beta * travel_time)`
+///     decayed score, appended after the cutoff scores.
+///
+/// Returns
+/// -------
+/// list[list[float]]
+///     One score vector per origin, in the same order as `origins`: one
+///     entry per cutoff, followed by the decayed score if `decay_beta` was
+///     given.
+///
+/// Raises
+/// ------
+/// ValueError
+///     If `weights` and `destinations` have different lengths.
+///
+/// Notes
+/// -----
+/// This function releases the GIL during computation to allow other Python threads to run.
+#[gen_stub_pyfunction]
+#[pyfunction]
+#[pyo3(signature = (transit_model, origins, destinations, weights, departure_time, max_transfers=3, cutoffs=vec![], decay_beta=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn accessibility(
+    py: Python<'_>,
+    transit_model: &PyTransitModel,
+    origins: Vec<PyTransitPoint>,
+    destinations: Vec<PyTransitPoint>,
+    weights: Vec<f64>,
+    departure_time: Time,
+    max_transfers: usize,
+    cutoffs: Vec<Time>,
+    decay_beta: Option<f64>,
+) -> PyResult<Vec<Vec<f64>>> {
+    if weights.len() != destinations.len() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "weights must have the same length as destinations",
+        ));
+    }
+
+    let origins: Vec<_> = origins.into_iter().map(|p| p.inner).collect();
+    let destinations: Vec<_> = destinations.into_iter().map(|p| p.inner).collect();
+
+    let scores = py.allow_threads(|| {
+        origins
+            .par_iter()
+            .enumerate()
+            .map(|(origin_idx, origin)| {
+                let travel_times = match multimodal_routing_one_to_many(
+                    &transit_model.model,
+                    origin,
+                    &destinations,
+                    departure_time,
+                    max_transfers,
+                    None,
+                    None,
+                ) {
+                    Ok(results) => results,
+                    Err(e) => {
+                        log::warn!(
+                            "Accessibility routing failed for origin {origin_idx}, error: {e}"
+                        );
+                        vec![None; destinations.len()]
+                    }
+                };
+
+                score_origin(&travel_times, &weights, &cutoffs, decay_beta)
+            })
+            .collect::<Vec<_>>()
+    });
+
+    Ok(scores)
+}
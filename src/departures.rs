@@ -0,0 +1,99 @@
+use ferrobus_core::prelude::*;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use pyo3_stub_gen::derive::gen_stub_pyfunction;
+
+use crate::model::PyTransitModel;
+
+/// Next `limit` departures from `stop_id` at or after `after_time`, grouped
+/// by route and then by headsign/direction, for building stop-board
+/// displays on top of the crate.
+///
+/// Returns a list of dicts shaped like::
+///
+///     {
+///         "route_id": str,
+///         "route_short_name": str,
+///         "route_long_name": str,
+///         "route_color": str | None,
+///         "headsign_groups": [
+///             {
+///                 "headsign": str,
+///                 "direction_id": int | None,
+///                 "departures": [{"trip_idx": int, "departure_time": int}, ...],
+///             },
+///             ...
+///         ],
+///     }
+#[pyfunction]
+#[gen_stub_pyfunction]
+pub fn nearby_departures(
+    py: Python<'_>,
+    transit_model: &PyTransitModel,
+    stop_id: RaptorStopId,
+    after_time: Time,
+    limit: usize,
+) -> PyResult<PyObject> {
+    let groups = transit_model
+        .model
+        .transit_data
+        .nearby_departures(stop_id, after_time, limit)
+        .map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                "Failed to compute nearby departures: {e}"
+            ))
+        })?;
+
+    Ok(route_groups_to_py(py, &groups).into())
+}
+
+fn route_groups_to_py(py: Python<'_>, groups: &[RouteGroup]) -> Bound<'_, PyList> {
+    PyList::new(
+        py,
+        groups.iter().map(|group| {
+            let dict = PyDict::new(py);
+            dict.set_item("route_id", &group.route_id).unwrap();
+            dict.set_item("route_short_name", &group.route_short_name)
+                .unwrap();
+            dict.set_item("route_long_name", &group.route_long_name)
+                .unwrap();
+            dict.set_item("route_color", &group.route_color).unwrap();
+            dict.set_item(
+                "headsign_groups",
+                headsign_groups_to_py(py, &group.headsign_groups),
+            )
+            .unwrap();
+            dict
+        }),
+    )
+    .unwrap()
+}
+
+fn headsign_groups_to_py(py: Python<'_>, groups: &[HeadsignGroup]) -> Bound<'_, PyList> {
+    PyList::new(
+        py,
+        groups.iter().map(|group| {
+            let dict = PyDict::new(py);
+            dict.set_item("headsign", &group.headsign).unwrap();
+            dict.set_item("direction_id", group.direction_id).unwrap();
+            dict.set_item("departures", departures_to_py(py, &group.departures))
+                .unwrap();
+            dict
+        }),
+    )
+    .unwrap()
+}
+
+fn departures_to_py(py: Python<'_>, departures: &[Departure]) -> Bound<'_, PyList> {
+    PyList::new(
+        py,
+        departures.iter().map(|departure| {
+            let dict = PyDict::new(py);
+            dict.set_item("trip_idx", departure.trip_idx).unwrap();
+            dict.set_item("departure_time", departure.departure_time)
+                .unwrap();
+            dict
+        }),
+    )
+    .unwrap()
+}
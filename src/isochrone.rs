@@ -1,10 +1,14 @@
+use ferrobus_core::Error;
 use ferrobus_core::prelude::*;
+use ferrobus_core::routing::raptor::AccessibilityFilter;
 use geo::Polygon;
+use hashbrown::HashMap;
 use pyo3::prelude::*;
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyfunction, gen_stub_pymethods};
 use wkt::{ToWkt, TryFromWkt};
 
 use crate::model::PyTransitModel;
+use crate::progress::{BatchProgress, CancellationToken, finish_batch};
 use crate::routing::PyTransitPoint;
 
 #[gen_stub_pyclass]
@@ -57,7 +61,9 @@ pub fn create_isochrone_index(
 }
 
 #[pyfunction]
+#[pyo3(signature = (transit_data, start, departure_time, max_transfers, cutoff, index, wheelchair_accessible=false, allow_unknown_accessibility=false))]
 #[gen_stub_pyfunction]
+#[allow(clippy::too_many_arguments)]
 pub fn calculate_isochrone(
     py: Python<'_>,
     transit_data: &PyTransitModel,
@@ -66,8 +72,12 @@ pub fn calculate_isochrone(
     max_transfers: usize,
     cutoff: Time,
     index: &PyIsochroneIndex,
+    wheelchair_accessible: bool,
+    allow_unknown_accessibility: bool,
 ) -> PyResult<String> {
     py.allow_threads(|| {
+        let accessibility =
+            wheelchair_accessible.then(|| AccessibilityFilter::new(allow_unknown_accessibility));
         let isochrone = ferrobus_core::algo::isochrone::calculate_isochrone(
             &transit_data.model,
             &start.inner,
@@ -75,6 +85,8 @@ pub fn calculate_isochrone(
             max_transfers,
             cutoff,
             &index.inner,
+            None,
+            accessibility.as_ref(),
         )
         .map_err(|e| {
             PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
@@ -87,35 +99,246 @@ pub fn calculate_isochrone(
 }
 
 #[pyfunction]
+#[pyo3(signature = (transit_data, start, departure_time, max_transfers, cutoffs, index, wheelchair_accessible=false, allow_unknown_accessibility=false))]
 #[gen_stub_pyfunction]
-#[allow(clippy::needless_pass_by_value)]
-pub fn calculate_bulk_isochrones(
+#[allow(clippy::too_many_arguments)]
+pub fn calculate_banded_isochrone(
+    py: Python<'_>,
+    transit_data: &PyTransitModel,
+    start: &PyTransitPoint,
+    departure_time: Time,
+    max_transfers: usize,
+    cutoffs: Vec<Time>,
+    index: &PyIsochroneIndex,
+    wheelchair_accessible: bool,
+    allow_unknown_accessibility: bool,
+) -> PyResult<Vec<String>> {
+    py.allow_threads(|| {
+        let accessibility =
+            wheelchair_accessible.then(|| AccessibilityFilter::new(allow_unknown_accessibility));
+        let bands = ferrobus_core::algo::isochrone::calculate_banded_isochrone(
+            &transit_data.model,
+            &start.inner,
+            departure_time,
+            max_transfers,
+            &cutoffs,
+            &index.inner,
+            None,
+            accessibility.as_ref(),
+        )
+        .map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                "Failed to calculate banded isochrone: {e}"
+            ))
+        })?;
+
+        Ok(bands.iter().map(|b| b.to_wkt().to_string()).collect())
+    })
+}
+
+/// [`calculate_banded_isochrone`], but returned as a single `GeoJSON`
+/// `FeatureCollection` (serialized to a string) with one feature per cutoff,
+/// each carrying its cutoff as a `max_time` property, computed from a single
+/// routing pass instead of one call per cutoff.
+#[pyfunction]
+#[pyo3(signature = (transit_data, start, departure_time, max_transfers, cutoffs, index, wheelchair_accessible=false, allow_unknown_accessibility=false))]
+#[gen_stub_pyfunction]
+#[allow(clippy::too_many_arguments)]
+pub fn calculate_banded_isochrone_geojson(
+    py: Python<'_>,
+    transit_data: &PyTransitModel,
+    start: &PyTransitPoint,
+    departure_time: Time,
+    max_transfers: usize,
+    cutoffs: Vec<Time>,
+    index: &PyIsochroneIndex,
+    wheelchair_accessible: bool,
+    allow_unknown_accessibility: bool,
+) -> PyResult<String> {
+    py.allow_threads(|| {
+        let accessibility =
+            wheelchair_accessible.then(|| AccessibilityFilter::new(allow_unknown_accessibility));
+        let feature_collection =
+            ferrobus_core::algo::isochrone::calculate_banded_isochrone_geojson(
+                &transit_data.model,
+                &start.inner,
+                departure_time,
+                max_transfers,
+                &cutoffs,
+                &index.inner,
+                None,
+                accessibility.as_ref(),
+            )
+            .map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                    "Failed to calculate banded isochrone: {e}"
+                ))
+            })?;
+
+        Ok(serde_json::to_string(&feature_collection).unwrap_or_default())
+    })
+}
+
+/// Cumulative-opportunity accessibility score from `start`, summing
+/// `weights` (keyed by H3 cell index as `u64`) over every grid cell
+/// reached within `cutoff`.
+#[pyfunction]
+#[pyo3(signature = (transit_data, start, departure_time, max_transfers, cutoff, weights, index, wheelchair_accessible=false, allow_unknown_accessibility=false))]
+#[gen_stub_pyfunction]
+#[allow(clippy::too_many_arguments)]
+pub fn cumulative_accessibility(
+    py: Python<'_>,
+    transit_data: &PyTransitModel,
+    start: &PyTransitPoint,
+    departure_time: Time,
+    max_transfers: usize,
+    cutoff: Time,
+    weights: HashMap<u64, f64>,
+    index: &PyIsochroneIndex,
+    wheelchair_accessible: bool,
+    allow_unknown_accessibility: bool,
+) -> PyResult<f64> {
+    py.allow_threads(|| {
+        let weights = cell_weights_from_u64(&weights)?;
+        let accessibility =
+            wheelchair_accessible.then(|| AccessibilityFilter::new(allow_unknown_accessibility));
+
+        ferrobus_core::algo::isochrone::cumulative_accessibility(
+            &transit_data.model,
+            &start.inner,
+            departure_time,
+            max_transfers,
+            cutoff,
+            &weights,
+            &index.inner,
+            None,
+            accessibility.as_ref(),
+        )
+        .map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                "Failed to calculate accessibility: {e}"
+            ))
+        })
+    })
+}
+
+/// [`cumulative_accessibility`] for several start points in parallel.
+#[pyfunction]
+#[pyo3(signature = (transit_data, starts, departure_time, max_transfers, cutoff, weights, index, wheelchair_accessible=false, allow_unknown_accessibility=false))]
+#[gen_stub_pyfunction]
+#[allow(clippy::needless_pass_by_value, clippy::too_many_arguments)]
+pub fn bulk_accessibility(
     py: Python<'_>,
     transit_data: &PyTransitModel,
     starts: Vec<PyTransitPoint>,
     departure_time: Time,
     max_transfers: usize,
     cutoff: Time,
+    weights: HashMap<u64, f64>,
     index: &PyIsochroneIndex,
-) -> PyResult<Vec<String>> {
+    wheelchair_accessible: bool,
+    allow_unknown_accessibility: bool,
+) -> PyResult<Vec<f64>> {
     py.allow_threads(|| {
+        let weights = cell_weights_from_u64(&weights)?;
         let inners = starts.iter().map(|p| &p.inner).collect::<Vec<_>>();
-        let isochrones = ferrobus_core::algo::isochrone::bulk_isochrones(
+        let accessibility =
+            wheelchair_accessible.then(|| AccessibilityFilter::new(allow_unknown_accessibility));
+
+        ferrobus_core::algo::isochrone::bulk_accessibility(
             &transit_data.model,
             inners.as_slice(),
             departure_time,
             max_transfers,
             cutoff,
+            &weights,
             &index.inner,
+            None,
+            accessibility.as_ref(),
         )
         .map_err(|e| {
             PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to calculate isochrone: {e}"
+                "Failed to calculate accessibility: {e}"
             ))
-        })?;
+        })
+    })
+}
 
-        let result = isochrones.iter().map(|i| i.to_wkt().to_string()).collect();
+/// Converts a Python-friendly `{cell_index: weight}` map (H3 cells as `u64`)
+/// into the `CellIndex`-keyed map the core accessibility functions expect.
+fn cell_weights_from_u64(weights: &HashMap<u64, f64>) -> PyResult<HashMap<h3o::CellIndex, f64>> {
+    weights
+        .iter()
+        .map(|(&cell, &weight)| {
+            h3o::CellIndex::try_from(cell)
+                .map(|cell| (cell, weight))
+                .map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                        "Invalid H3 cell index {cell}: {e}"
+                    ))
+                })
+        })
+        .collect()
+}
 
-        Ok(result)
-    })
+/// [`calculate_isochrone`] for several start points, run in parallel over a
+/// rayon pool.
+///
+/// `progress_callback`, if given, is called periodically as `callback(done,
+/// total)` counted in completed start points, throttled to avoid GIL
+/// contention with the rayon workers; if it raises, or if
+/// `cancellation_token` is cancelled, remaining start points are skipped and
+/// the call returns an error instead of a partial result.
+#[pyfunction]
+#[pyo3(signature = (transit_data, starts, departure_time, max_transfers, cutoff, index, wheelchair_accessible=false, allow_unknown_accessibility=false, progress_callback=None, cancellation_token=None))]
+#[gen_stub_pyfunction]
+#[allow(clippy::needless_pass_by_value, clippy::too_many_arguments)]
+pub fn calculate_bulk_isochrones(
+    py: Python<'_>,
+    transit_data: &PyTransitModel,
+    starts: Vec<PyTransitPoint>,
+    departure_time: Time,
+    max_transfers: usize,
+    cutoff: Time,
+    index: &PyIsochroneIndex,
+    wheelchair_accessible: bool,
+    allow_unknown_accessibility: bool,
+    progress_callback: Option<PyObject>,
+    cancellation_token: Option<CancellationToken>,
+) -> PyResult<Vec<String>> {
+    let progress = BatchProgress::new(progress_callback, cancellation_token, starts.len());
+
+    let isochrones = py.allow_threads(|| {
+        let inners = starts.iter().map(|p| &p.inner).collect::<Vec<_>>();
+        let accessibility =
+            wheelchair_accessible.then(|| AccessibilityFilter::new(allow_unknown_accessibility));
+
+        // Reimplemented here (rather than delegating to
+        // `ferrobus_core::algo::isochrone::bulk_isochrones`) so each start
+        // point is a chunk boundary the progress callback and cancellation
+        // token can observe.
+        inners
+            .par_iter()
+            .map(|start| {
+                if progress.is_cancelled() {
+                    return Err(Error::Cancelled);
+                }
+                let isochrone = ferrobus_core::algo::isochrone::calculate_isochrone(
+                    &transit_data.model,
+                    start,
+                    departure_time,
+                    max_transfers,
+                    cutoff,
+                    &index.inner,
+                    None,
+                    accessibility.as_ref(),
+                )?;
+                progress.tick();
+                Ok(isochrone)
+            })
+            .collect::<Result<Vec<_>, Error>>()
+    });
+    let isochrones = finish_batch(progress, isochrones, "Failed to calculate isochrone")?;
+
+    Ok(isochrones.iter().map(|i| i.to_wkt().to_string()).collect())
 }
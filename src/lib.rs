@@ -1,19 +1,29 @@
 use pyo3::prelude::*;
 use pyo3_stub_gen::define_stub_info_gatherer;
 
+use accessibility::accessibility;
+use departures::nearby_departures;
 use isochrone::{
-    PyIsochroneIndex, calculate_bulk_isochrones, calculate_isochrone, create_isochrone_index,
+    PyIsochroneIndex, bulk_accessibility, calculate_banded_isochrone,
+    calculate_banded_isochrone_geojson, calculate_bulk_isochrones, calculate_isochrone,
+    create_isochrone_index, cumulative_accessibility,
 };
 use matrix::travel_time_matrix;
 use model::{PyTransitModel, py_create_transit_model};
+use optimal_tour::optimal_tour;
+use progress::CancellationToken;
 use range_routing::{
     PyRangeRoutingResult, py_pareto_range_multimodal_routing, py_range_multimodal_routing,
 };
 use routing::{PyTransitPoint, create_transit_point, find_route, find_routes_one_to_many};
 
+pub mod accessibility;
+pub mod departures;
 pub mod isochrone;
 pub mod matrix;
 pub mod model;
+pub mod optimal_tour;
+pub mod progress;
 pub mod range_routing;
 pub mod routing;
 
@@ -26,16 +36,25 @@ fn ferrobus(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyTransitPoint>()?;
     m.add_function(wrap_pyfunction!(py_create_transit_model, m)?)?;
 
+    m.add_class::<CancellationToken>()?;
+
     m.add_function(wrap_pyfunction!(find_route, m)?)?;
     m.add_function(wrap_pyfunction!(find_routes_one_to_many, m)?)?;
     m.add_function(wrap_pyfunction!(create_transit_point, m)?)?;
 
     m.add_function(wrap_pyfunction!(travel_time_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(accessibility, m)?)?;
+    m.add_function(wrap_pyfunction!(optimal_tour, m)?)?;
+    m.add_function(wrap_pyfunction!(nearby_departures, m)?)?;
 
     m.add_class::<PyIsochroneIndex>()?;
     m.add_function(wrap_pyfunction!(create_isochrone_index, m)?)?;
     m.add_function(wrap_pyfunction!(calculate_isochrone, m)?)?;
     m.add_function(wrap_pyfunction!(calculate_bulk_isochrones, m)?)?;
+    m.add_function(wrap_pyfunction!(calculate_banded_isochrone, m)?)?;
+    m.add_function(wrap_pyfunction!(calculate_banded_isochrone_geojson, m)?)?;
+    m.add_function(wrap_pyfunction!(cumulative_accessibility, m)?)?;
+    m.add_function(wrap_pyfunction!(bulk_accessibility, m)?)?;
 
     m.add_class::<PyRangeRoutingResult>()?;
     m.add_function(wrap_pyfunction!(py_range_multimodal_routing, m)?)?;
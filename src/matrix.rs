@@ -1,48 +1,71 @@
+use ferrobus_core::Error;
 use ferrobus_core::prelude::*;
 use pyo3::prelude::*;
 use pyo3_stub_gen::derive::gen_stub_pyfunction;
 use rayon::prelude::*;
 
 use crate::model::PyTransitModel;
+use crate::progress::{BatchProgress, CancellationToken, finish_batch};
 use crate::routing::PyTransitPoint;
 
+/// Computes pairwise travel times between all `points`.
+///
+/// `progress_callback`, if given, is called periodically as `callback(done,
+/// total)` with `done`/`total` counted in completed origin rows, throttled
+/// to avoid GIL contention with the rayon workers; if it raises, the matrix
+/// computation stops at the next row boundary and the exception propagates.
+/// `cancellation_token` offers the same early-exit from outside the
+/// callback, e.g. from another thread.
 #[gen_stub_pyfunction]
+#[pyo3(signature = (transit_model, points, departure_time, max_transfers, progress_callback=None, cancellation_token=None))]
 #[pyfunction]
+#[allow(clippy::too_many_arguments)]
 pub fn travel_time_matrix(
     py: Python<'_>,
     transit_model: &PyTransitModel,
     points: Vec<PyTransitPoint>,
     departure_time: Time,
     max_transfers: usize,
+    progress_callback: Option<PyObject>,
+    cancellation_token: Option<CancellationToken>,
 ) -> PyResult<Vec<Vec<Option<u32>>>> {
     // Perform the routing
     let points: Vec<_> = points.into_iter().map(|p| p.inner).collect();
+    let progress = BatchProgress::new(progress_callback, cancellation_token, points.len());
+
     let full_vec = py.allow_threads(|| {
         points
             .par_iter()
             .map(|start_point| {
-                match multimodal_routing_one_to_many(
+                if progress.is_cancelled() {
+                    return Err(Error::Cancelled);
+                }
+
+                let row = match multimodal_routing_one_to_many(
                     &transit_model.model,
                     start_point,
                     &points,
                     departure_time,
                     max_transfers,
+                    None,
+                    None,
                 ) {
                     Ok(result) => result,
                     Err(e) => {
                         println!("Routing failed for point {start_point:?}, error: {e}");
                         vec![None; points.len()]
                     }
-                }
-            })
-            .map(|vector| {
-                vector
+                };
+                progress.tick();
+                Ok(row
                     .into_iter()
                     .map(|result| result.map(|dict| dict.travel_time))
-                    .collect::<Vec<_>>()
+                    .collect::<Vec<_>>())
             })
-            .collect::<Vec<_>>()
+            .collect::<Result<Vec<_>, Error>>()
     });
 
+    let full_vec = finish_batch(progress, full_vec, "Travel time matrix computation failed")?;
+
     Ok(full_vec)
 }
@@ -82,6 +82,20 @@ impl PyTransitModel {
 ///     If None, includes all services.
 /// max_transfer_time : int, default=1800
 ///     Maximum walking time in seconds allowed for transfers between stops
+/// build_contraction_hierarchy : bool, default=True
+///     Preprocess the street graph with contraction hierarchies so that
+///     repeated point-to-point walking queries (e.g. transfer-geometry
+///     reconstruction) run much faster. Costs an upfront pass over the
+///     whole street graph; set to False for a short-lived model that only
+///     issues a handful of queries.
+/// footpath_max_length : float, default=1000.0
+///     Straight-line cutoff, in meters, for considering a stop pair during
+///     footpath precomputation, independent of the walking-time cap
+///     imposed by max_transfer_time.
+/// footpath_cluster_size : float, default=100.0
+///     Grid cell size, in meters, used to cluster nearby stops during
+///     footpath precomputation: stops sharing a cell reuse a single
+///     Dijkstra tree instead of each running its own street search.
 ///
 /// Returns
 /// -------
@@ -98,13 +112,16 @@ impl PyTransitModel {
 /// The function releases the GIL during processing to allow other Python threads to continue execution.
 #[gen_stub_pyfunction]
 #[pyfunction(name = "create_transit_model")]
-#[pyo3(signature = (osm_path, gtfs_dirs, date, max_transfer_time = 1800))]
+#[pyo3(signature = (osm_path, gtfs_dirs, date, max_transfer_time = 1800, build_contraction_hierarchy = true, footpath_max_length = 1000.0, footpath_cluster_size = 100.0))]
 pub fn py_create_transit_model(
     py: Python<'_>,
     osm_path: &str,
     gtfs_dirs: Vec<String>,
     date: Option<chrono::NaiveDate>,
     max_transfer_time: u32,
+    build_contraction_hierarchy: bool,
+    footpath_max_length: f64,
+    footpath_cluster_size: f64,
 ) -> PyResult<PyTransitModel> {
     // Allow Python threads during all blocking operations
     py.allow_threads(|| {
@@ -117,8 +134,13 @@ pub fn py_create_transit_model(
         let config = TransitModelConfig {
             osm_path: osm_pathbuf,
             gtfs_dirs: gtfs_pathbufs,
+            day_of_week: String::new(),
             date,
             max_transfer_time,
+            need_transfer: None,
+            build_contraction_hierarchy,
+            footpath_max_length,
+            footpath_cluster_size,
         };
 
         // Create transit model
@@ -0,0 +1,213 @@
+use ferrobus_core::prelude::*;
+use pyo3::prelude::*;
+use pyo3_stub_gen::derive::gen_stub_pyfunction;
+
+use crate::model::PyTransitModel;
+use crate::routing::PyTransitPoint;
+
+/// Default for `held_karp_max_points`: instances at or below this many
+/// points are solved exactly with Held-Karp by default; larger instances
+/// fall back to nearest-neighbor + 2-opt, since Held-Karp's `O(2^n * n^2)`
+/// table becomes impractical well before `n` reaches 20.
+const DEFAULT_HELD_KARP_MAX_POINTS: usize = 13;
+
+/// Hard ceiling on `held_karp_max_points`, regardless of what a caller
+/// passes in: Held-Karp allocates a `dp`/`predecessor` table of size
+/// `O(2^n * n)`, so raising this much further risks an OOM abort rather
+/// than just a slow solve.
+const HELD_KARP_HARD_CEILING: usize = 20;
+
+/// Held-Karp dynamic programming over an asymmetric travel-time matrix, via
+/// [`ferrobus_core`]'s generic subset-DP helper.
+///
+/// `matrix[i][j]` is the travel time from point `i` to point `j`, or `None`
+/// if `j` is unreachable from `i`. Point `0` is fixed as the tour's start and
+/// every other point is visited exactly once; the tour does not return to
+/// the start. Returns the visiting order (starting with `0`) and its total
+/// time, or `None` if no complete tour exists.
+fn held_karp_matrix(matrix: &[Vec<Option<Time>>]) -> Option<(Vec<usize>, Time)> {
+    let n = matrix.len();
+    if n <= 1 {
+        return Some((vec![0], 0));
+    }
+    let rest = n - 1;
+
+    let (order, _, total_time) = held_karp(
+        rest,
+        |j| matrix[0][j + 1].map(|time| (time, ())),
+        |j, cost_at_j, k| matrix[j + 1][k + 1].map(|time| (cost_at_j + time, ())),
+    )?;
+
+    let mut full_order = vec![0];
+    full_order.extend(order.into_iter().map(|rest_idx| rest_idx + 1));
+    Some((full_order, total_time))
+}
+
+/// Total time of `order` under `matrix`, treating an unreachable leg as
+/// making the whole tour invalid.
+fn tour_time(matrix: &[Vec<Option<Time>>], order: &[usize]) -> Option<Time> {
+    order
+        .windows(2)
+        .try_fold(0, |acc, pair| Some(acc + matrix[pair[0]][pair[1]]?))
+}
+
+/// Builds an initial tour by always moving to the nearest unvisited point.
+fn nearest_neighbor_tour(matrix: &[Vec<Option<Time>>]) -> Option<Vec<usize>> {
+    let n = matrix.len();
+    let mut visited = vec![false; n];
+    let mut order = vec![0];
+    visited[0] = true;
+
+    for _ in 1..n {
+        let current = *order.last().unwrap();
+        let next = (0..n)
+            .filter(|&j| !visited[j])
+            .filter_map(|j| matrix[current][j].map(|time| (j, time)))
+            .min_by_key(|&(_, time)| time)?;
+        visited[next.0] = true;
+        order.push(next.0);
+    }
+
+    Some(order)
+}
+
+/// Repeatedly reverses a segment of `order` when doing so lowers the total
+/// tour time, until no such move helps.
+fn two_opt(matrix: &[Vec<Option<Time>>], mut order: Vec<usize>) -> Vec<usize> {
+    let n = order.len();
+    loop {
+        let mut improved = false;
+        for i in 1..n.saturating_sub(1) {
+            for j in (i + 1)..n {
+                let mut candidate = order.clone();
+                candidate[i..=j].reverse();
+                if let (Some(current_time), Some(candidate_time)) =
+                    (tour_time(matrix, &order), tour_time(matrix, &candidate))
+                {
+                    if candidate_time < current_time {
+                        order = candidate;
+                        improved = true;
+                    }
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    order
+}
+
+/// Find the best order to visit a set of transit-served points
+///
+/// Builds the asymmetric travel-time matrix between `points` via one-to-many
+/// routing, then solves the resulting open-path traveling-salesman problem:
+/// starting at `points[0]`, visit every other point exactly once at minimum
+/// total travel time. Small instances (at most `held_karp_max_points`
+/// points) are solved exactly with Held-Karp dynamic programming; larger
+/// instances fall back to a nearest-neighbor construction refined with
+/// 2-opt local search.
+///
+/// Parameters
+/// ----------
+/// transit_model : TransitModel
+///     The transit model to use for routing.
+/// points : list[TransitPoint]
+///     Points to visit; `points[0]` is fixed as the tour's start.
+/// departure_time : int
+///     Time of departure in seconds since midnight.
+/// max_transfers : int, default=3
+///     Maximum number of transfers allowed in route planning.
+/// held_karp_max_points : int, optional
+///     Instances at or below this many points are solved exactly with
+///     Held-Karp; larger instances fall back to nearest-neighbor + 2-opt.
+///     Defaults to 13, since Held-Karp's `O(2^n * n^2)` table becomes
+///     impractical well before `n` reaches 20. Capped at 20 regardless of
+///     what is passed in, since Held-Karp's table is exponential in this
+///     value and an uncapped one risks an OOM abort.
+///
+/// Returns
+/// -------
+/// tuple[list[int], int]
+///     The visiting order as indices into `points` (starting with `0`), and
+///     the tour's total travel time in seconds.
+///
+/// Raises
+/// ------
+/// ValueError
+///     If fewer than two points are given, or `held_karp_max_points`
+///     exceeds 20.
+/// RuntimeError
+///     If no complete tour exists because some point is unreachable from
+///     the others.
+///
+/// Notes
+/// -----
+/// This function releases the GIL during computation to allow other Python threads to run.
+#[gen_stub_pyfunction]
+#[pyfunction]
+#[pyo3(signature = (transit_model, points, departure_time, max_transfers=3, held_karp_max_points=None))]
+pub fn optimal_tour(
+    py: Python<'_>,
+    transit_model: &PyTransitModel,
+    points: Vec<PyTransitPoint>,
+    departure_time: Time,
+    max_transfers: usize,
+    held_karp_max_points: Option<usize>,
+) -> PyResult<(Vec<usize>, Time)> {
+    if points.len() < 2 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "optimal_tour requires at least two points",
+        ));
+    }
+    if held_karp_max_points.is_some_and(|n| n > HELD_KARP_HARD_CEILING) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "held_karp_max_points must be at most {HELD_KARP_HARD_CEILING}"
+        )));
+    }
+
+    let points: Vec<_> = points.into_iter().map(|p| p.inner).collect();
+
+    let matrix: Vec<Vec<Option<Time>>> = py.allow_threads(|| {
+        points
+            .iter()
+            .map(|start_point| {
+                match multimodal_routing_one_to_many(
+                    &transit_model.model,
+                    start_point,
+                    &points,
+                    departure_time,
+                    max_transfers,
+                    None,
+                    None,
+                ) {
+                    Ok(results) => results
+                        .into_iter()
+                        .map(|r| r.map(|r| r.travel_time))
+                        .collect(),
+                    Err(e) => {
+                        log::warn!("Routing failed for point {start_point:?}, error: {e}");
+                        vec![None; points.len()]
+                    }
+                }
+            })
+            .collect()
+    });
+
+    let held_karp_max_points = held_karp_max_points.unwrap_or(DEFAULT_HELD_KARP_MAX_POINTS);
+    let tour = if points.len() <= held_karp_max_points {
+        held_karp_matrix(&matrix)
+    } else {
+        nearest_neighbor_tour(&matrix).map(|order| {
+            let order = two_opt(&matrix, order);
+            let time = tour_time(&matrix, &order).unwrap_or(0);
+            (order, time)
+        })
+    };
+
+    tour.ok_or_else(|| {
+        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+            "No complete tour exists: some point is unreachable from the others",
+        )
+    })
+}
@@ -0,0 +1,171 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use pyo3::prelude::*;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+
+/// Minimum spacing between `progress_callback` invocations. Batch loops can
+/// complete thousands of items per second, and reacquiring the GIL on every
+/// single one would thrash it against the rayon worker threads; throttling
+/// to one report per interval keeps the callback's overhead negligible.
+const THROTTLE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A cooperative cancellation flag shared between Python and a running
+/// batch call (`travel_time_matrix`, `find_routes_one_to_many`,
+/// `calculate_bulk_isochrones`). Cancellation is checked at each chunk
+/// boundary inside the batch loop, not enforced immediately: in-flight work
+/// for the current chunk still completes before the call returns early.
+///
+/// Example:
+///
+/// .. code-block:: python
+///
+///     token = ferrobus.CancellationToken()
+///     # pass `token` to a batch call, then from another thread:
+///     token.cancel()
+#[gen_stub_pyclass]
+#[pyclass(name = "CancellationToken")]
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl CancellationToken {
+    #[new]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation of whichever batch call holds this token.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Shared state for a `progress_callback` and/or `cancellation_token` across
+/// a rayon-parallel batch loop. Created once per call (before
+/// `py.allow_threads`) and polled from worker threads via [`Self::tick`] /
+/// [`Self::is_cancelled`]; the GIL is only reacquired for the duration of an
+/// actual callback invocation.
+pub(crate) struct BatchProgress {
+    callback: Option<PyObject>,
+    token: Option<CancellationToken>,
+    total: usize,
+    completed: AtomicUsize,
+    last_reported: Mutex<Instant>,
+    callback_failed: AtomicBool,
+    failure: Mutex<Option<PyErr>>,
+}
+
+impl BatchProgress {
+    pub(crate) fn new(
+        callback: Option<PyObject>,
+        token: Option<CancellationToken>,
+        total: usize,
+    ) -> Self {
+        Self {
+            callback,
+            token,
+            total,
+            completed: AtomicUsize::new(0),
+            last_reported: Mutex::new(Instant::now() - THROTTLE_INTERVAL),
+            callback_failed: AtomicBool::new(false),
+            failure: Mutex::new(None),
+        }
+    }
+
+    /// True once the caller's cancellation token was set or the callback
+    /// raised on a prior tick. Check at each chunk boundary to skip the rest
+    /// of a batch cheaply.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.callback_failed.load(Ordering::Relaxed)
+            || self
+                .token
+                .as_ref()
+                .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Record one completed unit of work and, if due, report
+    /// `(completed, total)` to the callback.
+    pub(crate) fn tick(&self) {
+        self.tick_by(1);
+    }
+
+    /// Record `n` completed units of work (for batch loops that process
+    /// several items per chunk) and, if due, report `(completed, total)`.
+    pub(crate) fn tick_by(&self, n: usize) {
+        let completed = self.completed.fetch_add(n, Ordering::Relaxed) + n;
+
+        let Some(callback) = &self.callback else {
+            return;
+        };
+        if self.is_cancelled() {
+            return;
+        }
+
+        {
+            let mut last_reported = self.last_reported.lock().unwrap();
+            let now = Instant::now();
+            if now.duration_since(*last_reported) < THROTTLE_INTERVAL && completed < self.total {
+                return;
+            }
+            *last_reported = now;
+        }
+
+        Python::with_gil(|py| {
+            if let Err(e) = callback.call1(py, (completed, self.total)) {
+                self.callback_failed.store(true, Ordering::Relaxed);
+                *self.failure.lock().unwrap() = Some(e);
+            }
+        });
+    }
+
+    /// Consume `self`, returning the error the callback raised (if any) so
+    /// the caller can propagate it once the parallel loop has unwound.
+    pub(crate) fn into_result(self) -> PyResult<()> {
+        match self.failure.into_inner().unwrap() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// True once the callback has raised. Distinguishes, at a chunk
+    /// boundary's early exit, "the callback raised" from "the caller's
+    /// cancellation token was set" — [`Self::is_cancelled`] is true in both
+    /// cases, but only the former has a real exception to propagate instead
+    /// of a generic cancellation error.
+    pub(crate) fn callback_failed(&self) -> bool {
+        self.callback_failed.load(Ordering::Relaxed)
+    }
+}
+
+/// Turns the `Result` a batch loop's `py.allow_threads` closure returns into
+/// a `PyResult`, preferring the real exception a raising `progress_callback`
+/// stored in `progress` over the generic [`ferrobus_core::Error::Cancelled`]
+/// the loop's early-return produces when it can't otherwise tell a raising
+/// callback apart from a cancelled token.
+pub(crate) fn finish_batch<T>(
+    progress: BatchProgress,
+    result: Result<T, ferrobus_core::Error>,
+    context: &str,
+) -> PyResult<T> {
+    match result {
+        Ok(value) => {
+            progress.into_result()?;
+            Ok(value)
+        }
+        Err(ferrobus_core::Error::Cancelled) if progress.callback_failed() => {
+            Err(progress.into_result().unwrap_err())
+        }
+        Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+            "{context}: {e}"
+        ))),
+    }
+}
@@ -4,8 +4,16 @@ use pyo3::types::PyDict;
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyfunction, gen_stub_pymethods};
 
 use crate::model::PyTransitModel;
+use crate::progress::{BatchProgress, CancellationToken, finish_batch};
+use ferrobus_core::Error;
 use ferrobus_core::prelude::*;
 
+/// Destinations per `multimodal_routing_one_to_many` call in
+/// [`find_routes_one_to_many`]. Each chunk still shares a single RAPTOR run
+/// across its destinations, so chunking trades a small amount of that reuse
+/// for a progress/cancellation checkpoint between chunks.
+const PROGRESS_CHUNK_SIZE: usize = 200;
+
 /// # TransitPoint
 ///
 /// A geographic location connected to the transit network with pre-calculated access paths
@@ -247,17 +255,27 @@ pub fn find_route(
 ///     Each result is either a dictionary with route details or None if
 ///     the destination is unreachable.
 ///
+/// progress_callback : callable, optional
+///     Called periodically as `callback(done, total)`, with `done`/`total`
+///     counted in destinations, throttled to avoid GIL contention with the
+///     underlying rayon workers. If it raises, the batch stops at the next
+///     chunk boundary and the exception propagates.
+/// cancellation_token : CancellationToken, optional
+///     Offers the same early-exit as a raising `progress_callback`, but
+///     settable from outside the callback, e.g. from another thread.
+///
 /// Raises
 /// ------
 /// RuntimeError
-///     If the batch routing calculation fails.
+///     If the batch routing calculation fails, or is cancelled.
 ///
 /// Notes
 /// -----
 /// This function releases the GIL during computation to allow other Python threads to run.
 #[pyfunction]
 #[gen_stub_pyfunction]
-#[pyo3(signature = (transit_model, start_point, end_points, departure_time, max_transfers=3))]
+#[pyo3(signature = (transit_model, start_point, end_points, departure_time, max_transfers=3, progress_callback=None, cancellation_token=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn find_routes_one_to_many(
     py: Python<'_>,
     transit_model: &PyTransitModel,
@@ -265,25 +283,37 @@ pub fn find_routes_one_to_many(
     end_points: Vec<PyTransitPoint>,
     departure_time: Time,
     max_transfers: usize,
+    progress_callback: Option<PyObject>,
+    cancellation_token: Option<CancellationToken>,
 ) -> PyResult<Vec<PyObject>> {
     let end_points = end_points.into_iter().map(|p| p.inner).collect::<Vec<_>>();
+    let progress = BatchProgress::new(progress_callback, cancellation_token, end_points.len());
 
-    // Perform the routing
-    let results = py
-        .allow_threads(|| {
-            multimodal_routing_one_to_many(
+    // Perform the routing, one `multimodal_routing_one_to_many` call per
+    // chunk of destinations so progress/cancellation can be observed at
+    // each chunk boundary without giving up chunk-local RAPTOR run reuse.
+    let results = py.allow_threads(|| -> Result<Vec<_>, Error> {
+        let mut results = Vec::with_capacity(end_points.len());
+        for chunk in end_points.chunks(PROGRESS_CHUNK_SIZE) {
+            if progress.is_cancelled() {
+                return Err(Error::Cancelled);
+            }
+
+            let chunk_results = multimodal_routing_one_to_many(
                 &transit_model.model,
                 &start_point.inner,
-                &end_points,
+                chunk,
                 departure_time,
                 max_transfers,
-            )
-        })
-        .map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "One-to-many routing failed: {e}"
-            ))
-        })?;
+                None,
+                None,
+            )?;
+            results.extend(chunk_results);
+            progress.tick_by(chunk.len());
+        }
+        Ok(results)
+    });
+    let results = finish_batch(progress, results, "One-to-many routing failed")?;
 
     // Convert results to Python objects
     let py_results = results